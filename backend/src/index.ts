@@ -57,7 +57,7 @@ app.get('/api/casino/stats', async (req, res) => {
       totalFees: casinoAccount.totalFees.toString(),
       totalStaked: casinoAccount.totalStaked.toString(),
       houseEdgeBps: casinoAccount.houseEdgeBps,
-      isPaused: casinoAccount.isPaused,
+      pauseFlags: casinoAccount.pauseFlags,
     });
   } catch (error: any) {
     console.error('Error fetching casino stats:', error);
@@ -96,6 +96,7 @@ app.get('/api/games/:gameId', async (req, res) => {
       gameId: gameAccount.gameId.toString(),
       crashMultiplier: gameAccount.crashMultiplier.toString(),
       isResolved: gameAccount.isResolved,
+      status: Object.keys(gameAccount.status)[0],
     });
   } catch (error: any) {
     console.error('Error fetching game:', error);
@@ -168,13 +168,11 @@ const Game: React.FC = () => {
           game,
           bet,
           player: publicKey,
-          playerTokenAccount: publicKey,
-          vault: publicKey,
-          tokenProgram: new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
         })
         .rpc();
 
-      alert(`Cashed out at ${gameState.multiplier.toFixed(2)}x! Transaction: ${tx}`);
+      // Paid when the round is settled, if it crashes at or above this multiplier
+      alert(`Cashout locked at ${gameState.multiplier.toFixed(2)}x! Transaction: ${tx}`);
       setGameState((prev) => ({
         ...prev,
         isRunning: false,
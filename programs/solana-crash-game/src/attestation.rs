@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::CasinoError;
+
+/// Size of the ed25519 instruction header (signature count and padding)
+const ED25519_HEADER_LEN: usize = 2;
+
+/// Size of one set of signature offsets in an ed25519 instruction
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Instruction index meaning "this instruction" in ed25519 offsets
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Attestation message length, the player key followed by the expiry
+pub const ATTESTATION_MESSAGE_LEN: usize = 32 + 8;
+
+/// Message the compliance signer signs to attest a player until `expiry`
+pub fn attestation_message(player: &Pubkey, expiry: i64) -> [u8; ATTESTATION_MESSAGE_LEN] {
+    let mut message = [0u8; ATTESTATION_MESSAGE_LEN];
+    message[..32].copy_from_slice(player.as_ref());
+    message[32..].copy_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// Extract the signer and message from ed25519 program instruction data
+/// Only a single signature with all data inline in the same instruction is accepted
+pub fn parse_ed25519_instruction(data: &[u8]) -> Result<(Pubkey, Vec<u8>)> {
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        CasinoError::InvalidAttestation
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_offset = read_u16(offsets) as usize;
+    let signature_instruction_index = read_u16(offsets + 2);
+    let public_key_offset = read_u16(offsets + 4) as usize;
+    let public_key_instruction_index = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+    let message_instruction_index = read_u16(offsets + 12);
+
+    // Data living in another instruction could be swapped out from under the check
+    require!(
+        signature_instruction_index == CURRENT_INSTRUCTION
+            && public_key_instruction_index == CURRENT_INSTRUCTION
+            && message_instruction_index == CURRENT_INSTRUCTION,
+        CasinoError::InvalidAttestation
+    );
+    require!(
+        signature_offset.saturating_add(64) <= data.len(),
+        CasinoError::InvalidAttestation
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset.saturating_add(32))
+        .ok_or(CasinoError::InvalidAttestation)?;
+    let message = data
+        .get(message_offset..message_offset.saturating_add(message_size))
+        .ok_or(CasinoError::InvalidAttestation)?;
+
+    let signer = Pubkey::try_from(public_key).map_err(|_| CasinoError::InvalidAttestation)?;
+    Ok((signer, message.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build ed25519 instruction data the way the native program client lays it out
+    fn build_instruction(signer: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data = vec![1u8, 0u8];
+        for value in [
+            signature_offset,
+            instruction_index,
+            public_key_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_parse_attestation() {
+        let signer = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+        let message = attestation_message(&player, 1_700_000_000);
+        let data = build_instruction(&signer, &message, CURRENT_INSTRUCTION);
+
+        let (parsed_signer, parsed_message) = parse_ed25519_instruction(&data).unwrap();
+        assert_eq!(parsed_signer, signer);
+        assert_eq!(parsed_message, message.to_vec());
+    }
+
+    #[test]
+    fn test_rejects_data_from_other_instructions() {
+        let signer = Pubkey::new_unique();
+        let message = attestation_message(&Pubkey::new_unique(), 1);
+        let data = build_instruction(&signer, &message, 0);
+        assert!(parse_ed25519_instruction(&data).is_err());
+    }
+
+    #[test]
+    fn test_rejects_truncated_data() {
+        let signer = Pubkey::new_unique();
+        let message = attestation_message(&Pubkey::new_unique(), 1);
+        let data = build_instruction(&signer, &message, CURRENT_INSTRUCTION);
+        assert!(parse_ed25519_instruction(&data[..data.len() - 1]).is_err());
+        assert!(parse_ed25519_instruction(&data[..10]).is_err());
+    }
+}
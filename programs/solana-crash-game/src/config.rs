@@ -0,0 +1,272 @@
+use anchor_lang::prelude::*;
+
+use crate::crash::{MAX_CURVE_SCALE, MIN_CURVE_SCALE};
+use crate::errors::CasinoError;
+use crate::fees::FeeSplit;
+
+/// Minimum notice between queueing an economic change and executing it (48 hours)
+pub const CONFIG_CHANGE_DELAY: i64 = 2 * 24 * 60 * 60;
+
+/// Highest house edge the protocol allows (10%)
+pub const MAX_HOUSE_EDGE_BPS: u16 = 1000;
+
+/// Longest betting window after a round starts (1 hour)
+pub const MAX_BETTING_WINDOW: i64 = 60 * 60;
+
+/// Highest share of a compounded reward paid to the keeper (5%)
+pub const MAX_KEEPER_REWARD_BPS: u16 = 500;
+
+/// Default slots to wait for randomness before a round can be voided (~10 minutes)
+pub const DEFAULT_VRF_TIMEOUT_SLOTS: u64 = 1500;
+
+/// Randomness timeout bounds in slots (~1 minute to ~1 day)
+pub const MIN_VRF_TIMEOUT_SLOTS: u64 = 150;
+pub const MAX_VRF_TIMEOUT_SLOTS: u64 = 216000;
+
+/// Default circuit breaker, pause bets after losing 20% of the bankroll within a day
+pub const DEFAULT_BREAKER_THRESHOLD_BPS: u16 = 2000;
+pub const DEFAULT_BREAKER_WINDOW: i64 = 24 * 60 * 60;
+
+/// Circuit breaker window bounds (1 hour to 7 days)
+pub const MIN_BREAKER_WINDOW: i64 = 60 * 60;
+pub const MAX_BREAKER_WINDOW: i64 = 7 * 24 * 60 * 60;
+
+/// Pause flags, each pauses one class of operation
+pub const PAUSE_BETS: u8 = 1 << 0;
+pub const PAUSE_ROUNDS: u8 = 1 << 1;
+pub const PAUSE_CASHOUTS: u8 = 1 << 2;
+pub const PAUSE_STAKING: u8 = 1 << 3; // Stake, lock and LP deposits
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 4; // Unstakes and LP withdrawals
+pub const PAUSE_CLAIMS: u8 = 1 << 5;
+pub const PAUSE_ALL: u8 = PAUSE_BETS
+    | PAUSE_ROUNDS
+    | PAUSE_CASHOUTS
+    | PAUSE_STAKING
+    | PAUSE_WITHDRAWALS
+    | PAUSE_CLAIMS;
+
+/// Casino parameters to change, unset fields keep their current value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfigParams {
+    // Timelocked, only applied through queue_config_change
+    pub house_edge_bps: Option<u16>,
+    pub min_bet: Option<u64>,
+    pub max_bet: Option<u64>,
+    pub fee_split: Option<FeeSplit>,
+    pub curve_scale: Option<u32>,
+    // Applied immediately through update_config
+    pub max_payout: Option<u64>, // Gross payout cap per bet
+    pub max_exposure_bps: Option<u16>, // Round exposure cap as a share of the vault, 0 disables
+    pub betting_window: Option<i64>, // Seconds after a round starts that bets are accepted, 0 disables
+    pub keeper_reward_bps: Option<u16>, // Share of keeper-compounded rewards paid to the keeper
+}
+
+impl ConfigParams {
+    pub const LEN: usize = (1 + 2) + (1 + 8) + (1 + 8) + (1 + FeeSplit::LEN) + (1 + 4)
+        + (1 + 8) + (1 + 2) + (1 + 8) + (1 + 2);
+
+    /// True when no field is set
+    pub fn is_empty(&self) -> bool {
+        *self == ConfigParams::default()
+    }
+
+    /// True when any field that changes game economics is set
+    pub fn is_timelocked(&self) -> bool {
+        self.house_edge_bps.is_some()
+            || self.min_bet.is_some()
+            || self.max_bet.is_some()
+            || self.fee_split.is_some()
+            || self.curve_scale.is_some()
+    }
+
+    /// Keep only the values of `self` for fields that are set in `mask`
+    pub fn masked(&self, mask: &ConfigParams) -> ConfigParams {
+        ConfigParams {
+            house_edge_bps: mask.house_edge_bps.and(self.house_edge_bps),
+            min_bet: mask.min_bet.and(self.min_bet),
+            max_bet: mask.max_bet.and(self.max_bet),
+            fee_split: mask.fee_split.and(self.fee_split),
+            curve_scale: mask.curve_scale.and(self.curve_scale),
+            max_payout: mask.max_payout.and(self.max_payout),
+            max_exposure_bps: mask.max_exposure_bps.and(self.max_exposure_bps),
+            betting_window: mask.betting_window.and(self.betting_window),
+            keeper_reward_bps: mask.keeper_reward_bps.and(self.keeper_reward_bps),
+        }
+    }
+
+    /// Check each set field against protocol bounds
+    /// Limits that relate two fields are checked against `current` for whichever side is unchanged
+    pub fn validate(&self, current: &ConfigParams) -> Result<()> {
+        if let Some(house_edge_bps) = self.house_edge_bps {
+            require!(house_edge_bps <= MAX_HOUSE_EDGE_BPS, CasinoError::InvalidHouseEdge);
+        }
+
+        let min_bet = self.min_bet.or(current.min_bet).unwrap_or_default();
+        let max_bet = self.max_bet.or(current.max_bet).unwrap_or_default();
+        require!(min_bet > 0 && min_bet <= max_bet, CasinoError::InvalidBetLimits);
+
+        let max_payout = self.max_payout.or(current.max_payout).unwrap_or(u64::MAX);
+        require!(max_payout >= max_bet, CasinoError::InvalidMaxPayout);
+
+        if let Some(max_exposure_bps) = self.max_exposure_bps {
+            require!(max_exposure_bps <= 10000, CasinoError::InvalidExposureRatio);
+        }
+
+        if let Some(betting_window) = self.betting_window {
+            require!(
+                (0..=MAX_BETTING_WINDOW).contains(&betting_window),
+                CasinoError::InvalidBettingWindow
+            );
+        }
+
+        if let Some(keeper_reward_bps) = self.keeper_reward_bps {
+            require!(
+                keeper_reward_bps <= MAX_KEEPER_REWARD_BPS,
+                CasinoError::InvalidKeeperReward
+            );
+        }
+
+        if let Some(fee_split) = self.fee_split {
+            fee_split.validate()?;
+        }
+
+        if let Some(curve_scale) = self.curve_scale {
+            require!(
+                (MIN_CURVE_SCALE..=MAX_CURVE_SCALE).contains(&curve_scale),
+                CasinoError::InvalidCurveScale
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current() -> ConfigParams {
+        ConfigParams {
+            min_bet: Some(100),
+            max_bet: Some(1000),
+            max_payout: Some(100000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_empty_params() {
+        assert!(ConfigParams::default().is_empty());
+
+        let params = ConfigParams {
+            house_edge_bps: Some(200),
+            ..Default::default()
+        };
+        assert!(!params.is_empty());
+    }
+
+    #[test]
+    fn test_house_edge_bound() {
+        let params = ConfigParams {
+            house_edge_bps: Some(MAX_HOUSE_EDGE_BPS),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_ok());
+
+        let params = ConfigParams {
+            house_edge_bps: Some(MAX_HOUSE_EDGE_BPS + 1),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+    }
+
+    #[test]
+    fn test_bet_limits_checked_against_current() {
+        // Raising min above the current max is rejected
+        let params = ConfigParams {
+            min_bet: Some(2000),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+
+        // Unless max is raised in the same change
+        let params = ConfigParams {
+            min_bet: Some(2000),
+            max_bet: Some(5000),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_ok());
+    }
+
+    #[test]
+    fn test_max_payout_covers_max_bet() {
+        let params = ConfigParams {
+            max_bet: Some(200000),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+
+        let params = ConfigParams {
+            max_payout: Some(500),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+    }
+
+    #[test]
+    fn test_risk_bounds() {
+        let params = ConfigParams {
+            max_exposure_bps: Some(10001),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+
+        let params = ConfigParams {
+            betting_window: Some(MAX_BETTING_WINDOW + 1),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+
+        let params = ConfigParams {
+            keeper_reward_bps: Some(MAX_KEEPER_REWARD_BPS + 1),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+
+        let params = ConfigParams {
+            max_exposure_bps: Some(5000),
+            betting_window: Some(30),
+            keeper_reward_bps: Some(100),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_ok());
+        assert!(!params.is_timelocked());
+    }
+
+    #[test]
+    fn test_masked_keeps_only_set_fields() {
+        let mask = ConfigParams {
+            max_bet: Some(5000),
+            ..Default::default()
+        };
+        let before = current().masked(&mask);
+        assert_eq!(before.max_bet, Some(1000));
+        assert_eq!(before.min_bet, None);
+        assert_eq!(before.max_payout, None);
+    }
+
+    #[test]
+    fn test_curve_scale_bounds() {
+        let params = ConfigParams {
+            curve_scale: Some(MIN_CURVE_SCALE - 1),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_err());
+
+        let params = ConfigParams {
+            curve_scale: Some(MAX_CURVE_SCALE),
+            ..Default::default()
+        };
+        assert!(params.validate(&current()).is_ok());
+    }
+}
@@ -1,9 +1,28 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::CasinoError;
+
+/// Lowest crash point in basis points (1.01x)
+pub const MIN_CRASH_MULTIPLIER: u64 = 10100;
+
+/// Highest crash point in basis points (100x)
+pub const MAX_CRASH_MULTIPLIER: u64 = 1000000;
+
+/// Default curve scale factor in basis points (5.0)
+pub const DEFAULT_CURVE_SCALE: u32 = 50000;
+
+/// Curve scale bounds in basis points (1.0 to 20.0)
+pub const MIN_CURVE_SCALE: u32 = 10000;
+pub const MAX_CURVE_SCALE: u32 = 200000;
+
+/// Growth of the live multiplier per second in flight (6%)
+pub const LIVE_GROWTH_RATE: f64 = 0.06;
+
 /// Calculate crash multiplier from VRF result
 /// Returns multiplier in basis points (e.g., 15000 = 1.5x)
 /// Range: 1.01x (10100) to 100x (1000000)
-pub fn calculate_crash_multiplier(vrf_result: [u8; 32]) -> Result<u64> {
+/// `curve_scale` sets how steep the curve is, in basis points (50000 = 5.0)
+pub fn calculate_crash_multiplier(vrf_result: [u8; 32], curve_scale: u32) -> Result<u64> {
     // Convert VRF bytes to u64
     let vrf_u64 = u64::from_le_bytes([
         vrf_result[0], vrf_result[1], vrf_result[2], vrf_result[3],
@@ -18,17 +37,82 @@ pub fn calculate_crash_multiplier(vrf_result: [u8; 32]) -> Result<u64> {
     
     // Exponential distribution for crash curve
     // Lower values = higher multipliers (more likely to crash early)
-    let crash_value = 1.0 - (-normalized * 5.0).exp(); // Scale factor 5.0 for curve
+    let scale = (curve_scale as f64) / 10000.0;
+    let crash_value = 1.0 - (-normalized * scale).exp();
     
     // Map to [1.01, 100] in basis points
     let multiplier_bps = (10100.0 + (crash_value * 989900.0)) as u64;
     
     // Ensure within bounds
-    let multiplier = multiplier_bps.max(10100).min(1000000);
+    let multiplier = multiplier_bps.clamp(MIN_CRASH_MULTIPLIER, MAX_CRASH_MULTIPLIER);
     
     Ok(multiplier)
 }
 
+/// Split a cashout at `multiplier` into the player's net payout and the house fee
+/// The gross payout is capped at `max_payout` before the house edge is taken
+pub fn calculate_payout(
+    amount: u64,
+    multiplier: u64,
+    max_payout: u64,
+    house_edge_bps: u16,
+) -> Result<(u64, u64)> {
+    let gross_payout = amount
+        .checked_mul(multiplier)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow)?
+        .min(max_payout);
+
+    let house_fee = gross_payout
+        .checked_mul(house_edge_bps as u64)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow)?;
+
+    let net_payout = gross_payout
+        .checked_sub(house_fee)
+        .ok_or(CasinoError::MathOverflow)?;
+
+    Ok((net_payout, house_fee))
+}
+
+/// Split a free bet cashout at `multiplier`, only the profit over the voucher amount is paid
+/// The house edge is taken from the profit
+pub fn calculate_free_bet_payout(
+    amount: u64,
+    multiplier: u64,
+    max_payout: u64,
+    house_edge_bps: u16,
+) -> Result<(u64, u64)> {
+    let profit = amount
+        .checked_mul(multiplier)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow)?
+        .min(max_payout)
+        .saturating_sub(amount);
+
+    let house_fee = profit
+        .checked_mul(house_edge_bps as u64)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow)?;
+
+    let net_payout = profit
+        .checked_sub(house_fee)
+        .ok_or(CasinoError::MathOverflow)?;
+
+    Ok((net_payout, house_fee))
+}
+
+/// Multiplier a round in flight has climbed to after `elapsed_seconds`, in basis points
+/// Starts at 1.00x and grows exponentially, never past the max crash point
+pub fn calculate_live_multiplier(elapsed_seconds: i64) -> u64 {
+    if elapsed_seconds <= 0 {
+        return 10000;
+    }
+
+    let multiplier = ((elapsed_seconds as f64) * LIVE_GROWTH_RATE).exp() * 10000.0;
+    (multiplier as u64).min(MAX_CRASH_MULTIPLIER)
+}
+
 /// Simulate multiplier progression over time
 /// Used for frontend display
 /// Returns multiplier at given time (in basis points)
@@ -57,12 +141,43 @@ mod tests {
     fn test_crash_multiplier_range() {
         // Test minimum (all zeros)
         let min_vrf = [0u8; 32];
-        let min_mult = calculate_crash_multiplier(min_vrf).unwrap();
+        let min_mult = calculate_crash_multiplier(min_vrf, DEFAULT_CURVE_SCALE).unwrap();
         assert!(min_mult >= 10100); // At least 1.01x
         
         // Test maximum (all 0xFF)
         let max_vrf = [0xFFu8; 32];
-        let max_mult = calculate_crash_multiplier(max_vrf).unwrap();
+        let max_mult = calculate_crash_multiplier(max_vrf, DEFAULT_CURVE_SCALE).unwrap();
         assert!(max_mult <= 1000000); // At most 100x
     }
+
+    #[test]
+    fn test_live_multiplier_climbs() {
+        assert_eq!(calculate_live_multiplier(0), 10000);
+        assert_eq!(calculate_live_multiplier(-5), 10000);
+
+        // e^0.6 after 10 seconds
+        assert_eq!(calculate_live_multiplier(10), 18221);
+        assert!(calculate_live_multiplier(20) > calculate_live_multiplier(10));
+
+        // Capped at the max crash point
+        assert_eq!(calculate_live_multiplier(3600), MAX_CRASH_MULTIPLIER);
+    }
+
+    #[test]
+    fn test_payout_split_and_cap() {
+        // 2x on 1000 with a 2% edge
+        assert_eq!(calculate_payout(1000, 20000, u64::MAX, 200).unwrap(), (1960, 40));
+
+        // Gross payout capped before the edge is taken
+        assert_eq!(calculate_payout(1000, 20000, 1500, 200).unwrap(), (1470, 30));
+    }
+
+    #[test]
+    fn test_free_bet_pays_profit_only() {
+        // 3x on a 1000 voucher pays the 2000 profit less a 2% edge
+        assert_eq!(calculate_free_bet_payout(1000, 30000, u64::MAX, 200).unwrap(), (1960, 40));
+
+        // Nothing above the stake, nothing paid
+        assert_eq!(calculate_free_bet_payout(1000, 10000, u64::MAX, 200).unwrap(), (0, 0));
+    }
 }
@@ -34,4 +34,226 @@ pub enum CasinoError {
     
     #[msg("Invalid VRF result")]
     InvalidVrfResult,
+    
+    #[msg("Invalid liquidity amount")]
+    InvalidLiquidityAmount,
+    
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+    
+    #[msg("Withdrawal exceeds free vault balance")]
+    InsufficientFreeBalance,
+    
+    #[msg("Round has not settled yet")]
+    RoundNotSettled,
+    
+    #[msg("Unstaking requires request_unstake while a cooldown is set")]
+    UnstakeCooldownRequired,
+    
+    #[msg("Unstake cooldown has not ended")]
+    UnstakeCooldownActive,
+    
+    #[msg("No pending unstake request")]
+    NoUnstakeRequest,
+    
+    #[msg("Invalid cooldown")]
+    InvalidCooldown,
+    
+    #[msg("Lock duration must be between 1 week and 1 year")]
+    InvalidLockDuration,
+    
+    #[msg("Stake is locked")]
+    StakeLocked,
+    
+    #[msg("Stake already has an active lock")]
+    LockAlreadyActive,
+    
+    #[msg("Stake has no active lock")]
+    NoActiveLock,
+    
+    #[msg("Auto-compounding is not enabled for this stake")]
+    AutoCompoundDisabled,
+    
+    #[msg("Staked token cannot be minted from fee rewards")]
+    CompoundUnavailable,
+    
+    #[msg("Fee split must add up to 10000 bps")]
+    InvalidFeeSplit,
+    
+    #[msg("Invalid withdraw amount")]
+    InvalidWithdrawAmount,
+    
+    #[msg("Insufficient treasury balance")]
+    InsufficientTreasury,
+    
+    #[msg("Treasury withdrawal limit for this epoch exceeded")]
+    TreasuryLimitExceeded,
+    
+    #[msg("Invalid reward stream")]
+    InvalidRewardStream,
+    
+    #[msg("Too many reward streams")]
+    TooManyRewardStreams,
+    
+    #[msg("Stake is represented by a position token")]
+    PositionTokenized,
+    
+    #[msg("Signer is not the pending authority")]
+    NoPendingAuthority,
+    
+    #[msg("Role cannot be assigned this way")]
+    InvalidRole,
+    
+    #[msg("House edge exceeds the protocol maximum")]
+    InvalidHouseEdge,
+    
+    #[msg("Min bet must be positive and not exceed max bet")]
+    InvalidBetLimits,
+    
+    #[msg("Crash curve scale out of bounds")]
+    InvalidCurveScale,
+    
+    #[msg("Config change sets no parameters")]
+    EmptyConfigChange,
+    
+    #[msg("Config change delay has not passed")]
+    ConfigChangeNotReady,
+    
+    #[msg("Parameter can only change through the config timelock")]
+    ConfigTimelocked,
+    
+    #[msg("Max payout must cover the max bet")]
+    InvalidMaxPayout,
+    
+    #[msg("Exposure ratio exceeds 100%")]
+    InvalidExposureRatio,
+    
+    #[msg("Betting window out of bounds")]
+    InvalidBettingWindow,
+    
+    #[msg("Keeper reward exceeds the protocol maximum")]
+    InvalidKeeperReward,
+    
+    #[msg("Bet would exceed the round exposure limit")]
+    ExposureLimitExceeded,
+    
+    #[msg("Betting window for this round has closed")]
+    BettingClosed,
+    
+    #[msg("Unknown or empty pause flags")]
+    InvalidPauseFlags,
+    
+    #[msg("Account cannot be migrated")]
+    InvalidMigration,
+    
+    #[msg("Account is already on the current layout")]
+    AlreadyMigrated,
+    
+    #[msg("Casino has been shut down")]
+    CasinoShutdown,
+    
+    #[msg("Bet is not eligible for a refund")]
+    BetNotRefundable,
+    
+    #[msg("Round is not awaiting randomness")]
+    RoundNotAwaitingRandomness,
+    
+    #[msg("Randomness request has not timed out")]
+    RandomnessNotExpired,
+    
+    #[msg("Round was voided")]
+    RoundVoided,
+    
+    #[msg("Randomness timeout out of bounds")]
+    InvalidVrfTimeout,
+    
+    #[msg("Circuit breaker settings out of bounds")]
+    InvalidCircuitBreaker,
+    
+    #[msg("Invalid player limits")]
+    InvalidPlayerLimits,
+    
+    #[msg("Player is self-excluded")]
+    SelfExcluded,
+    
+    #[msg("Cool-off period since the last bet has not passed")]
+    CoolOffActive,
+    
+    #[msg("Bet would exceed the daily wager limit")]
+    DailyWagerLimitExceeded,
+    
+    #[msg("Bet would exceed the daily loss limit")]
+    DailyLossLimitExceeded,
+    
+    #[msg("Gated casino requires a compliance attestation")]
+    AttestationRequired,
+    
+    #[msg("Compliance attestation has expired")]
+    AttestationExpired,
+    
+    #[msg("Invalid compliance attestation")]
+    InvalidAttestation,
+    
+    #[msg("Gated mode requires a compliance signer")]
+    InvalidComplianceSigner,
+    
+    #[msg("Round has not been resolved")]
+    GameNotResolved,
+    
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    
+    #[msg("Referrer already registered")]
+    ReferrerAlreadySet,
+    
+    #[msg("Referrer account required for a referred player")]
+    ReferrerAccountRequired,
+    
+    #[msg("Referral rate out of bounds")]
+    InvalidReferralRate,
+    
+    #[msg("Referral tier out of bounds")]
+    InvalidReferralTier,
+    
+    #[msg("Invalid VIP tiers")]
+    InvalidVipTiers,
+    
+    #[msg("Invalid free bet voucher")]
+    InvalidFreeBet,
+    
+    #[msg("Free bet voucher already used")]
+    FreeBetUsed,
+    
+    #[msg("Free bet voucher has expired")]
+    FreeBetExpired,
+    
+    #[msg("Below the free bet's minimum cashout multiplier")]
+    FreeBetMultiplierTooLow,
+    
+    #[msg("No jackpot to settle")]
+    NoJackpot,
+    
+    #[msg("Jackpot share already settled")]
+    JackpotAlreadySettled,
+    
+    #[msg("Jackpot trigger out of bounds")]
+    InvalidJackpotTrigger,
+    
+    #[msg("Bankroll is empty while LP shares are outstanding")]
+    EmptyBankroll,
+    
+    #[msg("Round is not in flight yet")]
+    RoundNotInFlight,
+    
+    #[msg("Round has not reached that multiplier")]
+    MultiplierNotReached,
+    
+    #[msg("Cashout already locked for this bet")]
+    CashoutAlreadyLocked,
+    
+    #[msg("Cashout is above the bet's auto-cashout")]
+    AboveAutoCashout,
+    
+    #[msg("LP mint account required for staked bankroll shares")]
+    LpMintRequired,
 }
@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::CasinoError;
+
+/// Number of referral tiers, each with its own share of referred players' fees
+pub const REFERRAL_TIER_COUNT: usize = 4;
+
+/// Share of each house fee routed to each bucket, in basis points (sums to 10000)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub staker_bps: u16,
+    pub treasury_bps: u16,
+    pub referral_bps: u16,
+    pub jackpot_bps: u16,
+}
+
+impl FeeSplit {
+    pub const LEN: usize = 2 + 2 + 2 + 2;
+
+    /// All fees go to stakers
+    pub const STAKERS_ONLY: FeeSplit = FeeSplit {
+        staker_bps: 10000,
+        treasury_bps: 0,
+        referral_bps: 0,
+        jackpot_bps: 0,
+    };
+
+    /// Check the buckets add up to the whole fee
+    pub fn validate(&self) -> Result<()> {
+        let total = self.staker_bps as u32
+            + self.treasury_bps as u32
+            + self.referral_bps as u32
+            + self.jackpot_bps as u32;
+        require!(total == 10000, CasinoError::InvalidFeeSplit);
+        Ok(())
+    }
+
+    /// Split a fee into buckets, rounding dust goes to stakers
+    pub fn split(&self, fee: u64) -> Result<FeeAllocation> {
+        let treasury = bps_of(fee, self.treasury_bps)?;
+        let referral = bps_of(fee, self.referral_bps)?;
+        let jackpot = bps_of(fee, self.jackpot_bps)?;
+        let staker = fee
+            .checked_sub(treasury)
+            .and_then(|x| x.checked_sub(referral))
+            .and_then(|x| x.checked_sub(jackpot))
+            .ok_or(CasinoError::MathOverflow)?;
+
+        Ok(FeeAllocation {
+            staker,
+            treasury,
+            referral,
+            jackpot,
+        })
+    }
+}
+
+/// A fee split into its buckets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeAllocation {
+    pub staker: u64,
+    pub treasury: u64,
+    pub referral: u64,
+    pub jackpot: u64,
+}
+
+/// Calculate a basis-point share of an amount
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    amount
+        .checked_mul(bps as u64)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow.into())
+}
+
+/// Referrer's credit for a fee at `rate_bps`, capped at the fee's referral bucket portion
+pub fn calculate_referral_credit(fee: u64, referral_portion: u64, rate_bps: u16) -> Result<u64> {
+    Ok(bps_of(fee, rate_bps)?.min(referral_portion))
+}
+
+/// A bettor's pro-rata share of a round's jackpot by stake
+pub fn calculate_jackpot_share(jackpot: u64, stake: u64, total_wagered: u64) -> Result<u64> {
+    if total_wagered == 0 {
+        return Ok(0);
+    }
+
+    let share = (jackpot as u128)
+        .checked_mul(stake as u128)
+        .and_then(|x| x.checked_div(total_wagered as u128))
+        .ok_or(CasinoError::MathOverflow)?;
+    u64::try_from(share).map_err(|_| CasinoError::MathOverflow.into())
+}
+
+/// Calculate how much the treasury may still withdraw in the current epoch
+/// The spent amount resets whenever a new epoch starts
+pub fn calculate_epoch_allowance(
+    epoch_limit: u64,
+    withdrawn_in_epoch: u64,
+    last_epoch: u64,
+    current_epoch: u64,
+) -> u64 {
+    if current_epoch != last_epoch {
+        return epoch_limit;
+    }
+    epoch_limit.saturating_sub(withdrawn_in_epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_split_validation() {
+        assert!(FeeSplit::STAKERS_ONLY.validate().is_ok());
+
+        let split = FeeSplit {
+            staker_bps: 6000,
+            treasury_bps: 2000,
+            referral_bps: 1000,
+            jackpot_bps: 500,
+        };
+        assert!(split.validate().is_err());
+    }
+
+    #[test]
+    fn test_fee_split_dust_goes_to_stakers() {
+        let split = FeeSplit {
+            staker_bps: 6000,
+            treasury_bps: 2000,
+            referral_bps: 1000,
+            jackpot_bps: 1000,
+        };
+        let allocation = split.split(1005).unwrap();
+        assert_eq!(allocation.treasury, 201);
+        assert_eq!(allocation.referral, 100);
+        assert_eq!(allocation.jackpot, 100);
+        assert_eq!(allocation.staker, 604);
+    }
+
+    #[test]
+    fn test_epoch_allowance_resets() {
+        assert_eq!(calculate_epoch_allowance(1000, 400, 7, 7), 600);
+        assert_eq!(calculate_epoch_allowance(1000, 1000, 7, 7), 0);
+        assert_eq!(calculate_epoch_allowance(1000, 1000, 7, 8), 1000);
+    }
+
+    #[test]
+    fn test_referral_credit_capped_by_bucket() {
+        // 10% of a 1000 fee fits inside a 200 referral portion
+        assert_eq!(calculate_referral_credit(1000, 200, 1000).unwrap(), 100);
+
+        // 50% would exceed it
+        assert_eq!(calculate_referral_credit(1000, 200, 5000).unwrap(), 200);
+    }
+
+    #[test]
+    fn test_jackpot_share_pro_rata() {
+        assert_eq!(calculate_jackpot_share(1000, 250, 1000).unwrap(), 250);
+        assert_eq!(calculate_jackpot_share(1000, 1, 3).unwrap(), 333);
+        assert_eq!(calculate_jackpot_share(1000, 0, 0).unwrap(), 0);
+    }
+}
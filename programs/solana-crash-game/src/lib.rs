@@ -1,13 +1,31 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 
 pub mod crash;
 pub mod staking;
+pub mod liquidity;
+pub mod fees;
+pub mod config;
+pub mod migration;
+pub mod limits;
+pub mod vip;
+pub mod attestation;
 pub mod vrf;
 pub mod errors;
 
 use crash::*;
 use staking::*;
+use liquidity::*;
+use fees::*;
+use config::*;
+use migration::*;
+use limits::*;
+use vip::*;
+use attestation::*;
 use vrf::*;
 use errors::*;
 
@@ -24,15 +42,61 @@ pub mod solana_crash_game {
         min_bet: u64,
         max_bet: u64,
     ) -> Result<()> {
+        require!(house_edge_bps <= MAX_HOUSE_EDGE_BPS, CasinoError::InvalidHouseEdge);
+        require!(min_bet > 0 && min_bet <= max_bet, CasinoError::InvalidBetLimits);
+
         let casino = &mut ctx.accounts.casino;
         casino.authority = ctx.accounts.authority.key();
         casino.house_edge_bps = house_edge_bps;
         casino.min_bet = min_bet;
         casino.max_bet = max_bet;
-        casino.is_paused = false;
+        casino.pause_flags = 0;
         casino.bump = ctx.bumps.casino;
+        casino.version = ACCOUNT_VERSION;
         casino.total_volume = 0;
         casino.total_fees = 0;
+        casino.round_exposure = 0;
+        casino.unstake_cooldown = 0;
+        casino.fee_split = FeeSplit::STAKERS_ONLY;
+        casino.staker_pool = 0;
+        casino.treasury_pool = 0;
+        casino.referral_pool = 0;
+        casino.jackpot_pool = 0;
+        casino.treasurer = ctx.accounts.authority.key();
+        casino.treasury_epoch_limit = 0;
+        casino.treasury_epoch = 0;
+        casino.treasury_withdrawn_in_epoch = 0;
+        casino.treasury_withdrawal_count = 0;
+        casino.reward_stream_count = 0;
+        casino.pending_authority = None;
+        casino.operator = ctx.accounts.authority.key();
+        casino.guardian = ctx.accounts.authority.key();
+        casino.risk_manager = ctx.accounts.authority.key();
+        casino.curve_scale = DEFAULT_CURVE_SCALE;
+        casino.max_payout = u64::MAX;
+        casino.max_exposure_bps = 0;
+        casino.betting_window = 0;
+        casino.round_started_at = 0;
+        casino.keeper_reward_bps = 0;
+        casino.is_shutdown = false;
+        casino.shutdown_game_id = 0;
+        casino.round_wagered = 0;
+        casino.refund_liability = 0;
+        casino.vrf_timeout_slots = DEFAULT_VRF_TIMEOUT_SLOTS;
+        casino.current_round_voided = false;
+        casino.breaker_threshold_bps = DEFAULT_BREAKER_THRESHOLD_BPS;
+        casino.breaker_window = DEFAULT_BREAKER_WINDOW;
+        casino.breaker_window_start = 0;
+        casino.breaker_window_bankroll = 0;
+        casino.breaker_net_flow = 0;
+        casino.compliance_signer = Pubkey::default();
+        casino.gated = false;
+        casino.referral_rates_bps = [0; REFERRAL_TIER_COUNT];
+        casino.vip_tiers = [VipTier::default(); VIP_TIER_COUNT];
+        casino.rakeback_pool = 0;
+        casino.promo = ctx.accounts.authority.key();
+        casino.free_bet_count = 0;
+        casino.jackpot_trigger_multiplier = 0;
         
         msg!("Casino initialized with house edge: {} bps", house_edge_bps);
         Ok(())
@@ -44,12 +108,50 @@ pub mod solana_crash_game {
         amount: u64,
         auto_cashout_multiplier: Option<u64>, // Optional auto-cashout (in basis points, e.g., 15000 = 1.5x)
     ) -> Result<()> {
-        require!(!ctx.accounts.casino.is_paused, CasinoError::GamePaused);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.casino.require_betting_open(now)?;
+        ctx.accounts.casino.require_attestation(ctx.accounts.player_attestation.as_deref(), now)?;
         require!(
-            amount >= ctx.accounts.casino.min_bet && amount <= ctx.accounts.casino.max_bet,
+            ctx.accounts.game.status == GameStatus::AwaitingRandomness,
+            CasinoError::BettingClosed
+        );
+
+        let vip_max_bet = vip_tier(&ctx.accounts.casino.vip_tiers, ctx.accounts.player_profile.vip_level)
+            .map_or(0, |tier| tier.max_bet);
+        require!(
+            amount >= ctx.accounts.casino.min_bet
+                && amount <= ctx.accounts.casino.max_bet.max(vip_max_bet),
             CasinoError::InvalidBetAmount
         );
 
+        // Responsible-gambling limits, the stricter of the player's and the operator's apply
+        let player_limits = &mut ctx.accounts.player_limits;
+        if player_limits.player == Pubkey::default() {
+            player_limits.player = ctx.accounts.player.key();
+            player_limits.bump = ctx.bumps.player_limits;
+            player_limits.version = ACCOUNT_VERSION;
+        }
+        player_limits.refresh(now);
+        player_limits.effective_limits().check_bet(
+            amount,
+            player_limits.wagered_today,
+            player_limits.lost_today,
+            player_limits.last_bet_at,
+            now,
+        )?;
+        player_limits.wagered_today = player_limits.wagered_today.saturating_add(amount);
+        player_limits.lost_today = player_limits.lost_today.saturating_add(amount);
+        player_limits.last_bet_at = now;
+
+        let player_profile = &mut ctx.accounts.player_profile;
+        if player_profile.player == Pubkey::default() {
+            player_profile.player = ctx.accounts.player.key();
+            player_profile.bump = ctx.bumps.player_profile;
+            player_profile.version = ACCOUNT_VERSION;
+        }
+        player_profile.record_bet(amount, Clock::get()?.slot);
+        player_profile.vip_level = vip_level(&ctx.accounts.casino.vip_tiers, player_profile.total_wagered);
+
         let bet = &mut ctx.accounts.bet;
         bet.player = ctx.accounts.player.key();
         bet.amount = amount;
@@ -57,6 +159,8 @@ pub mod solana_crash_game {
         bet.status = BetStatus::Pending;
         bet.game_id = ctx.accounts.casino.current_game_id;
         bet.bump = ctx.bumps.bet;
+        bet.version = ACCOUNT_VERSION;
+        bet.max_payout = ctx.accounts.casino.max_payout;
 
         // Transfer bet amount to vault
         let cpi_accounts = Transfer {
@@ -73,6 +177,38 @@ pub mod solana_crash_game {
             .checked_add(amount)
             .ok_or(CasinoError::MathOverflow)?;
 
+        ctx.accounts.casino.round_wagered = ctx.accounts.casino.round_wagered
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        ctx.accounts.game.total_wagered = ctx.accounts.game.total_wagered
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        let vault_balance = ctx.accounts.vault.amount;
+        ctx.accounts.casino.record_house_flow(
+            i64::try_from(amount).unwrap_or(i64::MAX),
+            vault_balance,
+            now,
+        );
+
+        // Track the worst-case payout against the bankroll, per round and across all pending bets
+        let exposure = bet.exposure()?;
+        ctx.accounts.game.exposure = ctx.accounts.game.exposure
+            .checked_add(exposure)
+            .ok_or(CasinoError::MathOverflow)?;
+        ctx.accounts.casino.round_exposure = ctx.accounts.casino.round_exposure
+            .checked_add(exposure)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        // Cap the round's exposure at a share of the bankroll held before this bet
+        let max_exposure_bps = ctx.accounts.casino.max_exposure_bps;
+        if max_exposure_bps > 0 {
+            require!(
+                ctx.accounts.game.exposure <= bps_of(ctx.accounts.vault.amount, max_exposure_bps)?,
+                CasinoError::ExposureLimitExceeded
+            );
+        }
+
         emit!(BetPlaced {
             player: bet.player,
             amount: bet.amount,
@@ -83,9 +219,147 @@ pub mod solana_crash_game {
         Ok(())
     }
 
+    /// Promo: Issue a free bet voucher to a player
+    pub fn issue_free_bet(
+        ctx: Context<IssueFreeBet>,
+        amount: u64,
+        expiry: i64,
+        min_cashout_multiplier: u64,
+    ) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Promo, &ctx.accounts.authority.key())?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            amount >= ctx.accounts.casino.min_bet && amount <= ctx.accounts.casino.max_bet,
+            CasinoError::InvalidBetAmount
+        );
+        require!(
+            expiry > now
+                && min_cashout_multiplier > 10000
+                && min_cashout_multiplier <= MAX_CRASH_MULTIPLIER,
+            CasinoError::InvalidFreeBet
+        );
+
+        let free_bet = &mut ctx.accounts.free_bet;
+        free_bet.id = ctx.accounts.casino.free_bet_count;
+        free_bet.player = ctx.accounts.player.key();
+        free_bet.amount = amount;
+        free_bet.expiry = expiry;
+        free_bet.min_cashout_multiplier = min_cashout_multiplier;
+        free_bet.is_used = false;
+        free_bet.bump = ctx.bumps.free_bet;
+        free_bet.version = ACCOUNT_VERSION;
+
+        ctx.accounts.casino.free_bet_count = ctx.accounts.casino.free_bet_count
+            .checked_add(1)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(FreeBetIssued {
+            id: free_bet.id,
+            player: free_bet.player,
+            amount,
+            expiry,
+            min_cashout_multiplier,
+        });
+
+        Ok(())
+    }
+
+    /// Place a bet with a free bet voucher instead of tokens
+    /// Cashouts below the voucher's minimum multiplier are rejected and only profit is paid
+    pub fn place_free_bet(
+        ctx: Context<PlaceFreeBet>,
+        auto_cashout_multiplier: Option<u64>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.casino.require_betting_open(now)?;
+        ctx.accounts.casino.require_attestation(ctx.accounts.player_attestation.as_deref(), now)?;
+        require!(
+            ctx.accounts.game.status == GameStatus::AwaitingRandomness,
+            CasinoError::BettingClosed
+        );
+
+        let free_bet = &mut ctx.accounts.free_bet;
+        require!(!free_bet.is_used, CasinoError::FreeBetUsed);
+        require!(now < free_bet.expiry, CasinoError::FreeBetExpired);
+        if let Some(multiplier) = auto_cashout_multiplier {
+            require!(
+                multiplier >= free_bet.min_cashout_multiplier,
+                CasinoError::FreeBetMultiplierTooLow
+            );
+        }
+        free_bet.is_used = true;
+
+        // Exclusions and cool-offs still apply, the voucher counts toward no wager or loss cap
+        let player_limits = &mut ctx.accounts.player_limits;
+        if player_limits.player == Pubkey::default() {
+            player_limits.player = ctx.accounts.player.key();
+            player_limits.bump = ctx.bumps.player_limits;
+            player_limits.version = ACCOUNT_VERSION;
+        }
+        player_limits.refresh(now);
+        player_limits.effective_limits().check_bet(
+            0,
+            player_limits.wagered_today,
+            player_limits.lost_today,
+            player_limits.last_bet_at,
+            now,
+        )?;
+        player_limits.last_bet_at = now;
+
+        let bet = &mut ctx.accounts.bet;
+        bet.player = ctx.accounts.player.key();
+        bet.amount = free_bet.amount;
+        bet.auto_cashout_multiplier = auto_cashout_multiplier;
+        bet.status = BetStatus::Pending;
+        bet.game_id = ctx.accounts.casino.current_game_id;
+        bet.bump = ctx.bumps.bet;
+        bet.version = ACCOUNT_VERSION;
+        bet.max_payout = ctx.accounts.casino.max_payout;
+        bet.is_free_bet = true;
+        bet.min_cashout_multiplier = free_bet.min_cashout_multiplier;
+
+        // Free bets add exposure but no stake to the round
+        let exposure = bet.exposure()?;
+        ctx.accounts.game.exposure = ctx.accounts.game.exposure
+            .checked_add(exposure)
+            .ok_or(CasinoError::MathOverflow)?;
+        ctx.accounts.casino.round_exposure = ctx.accounts.casino.round_exposure
+            .checked_add(exposure)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        let max_exposure_bps = ctx.accounts.casino.max_exposure_bps;
+        if max_exposure_bps > 0 {
+            require!(
+                ctx.accounts.game.exposure <= bps_of(ctx.accounts.vault.amount, max_exposure_bps)?,
+                CasinoError::ExposureLimitExceeded
+            );
+        }
+
+        emit!(FreeBetRedeemed {
+            id: free_bet.id,
+            player: bet.player,
+            amount: bet.amount,
+            game_id: bet.game_id,
+        });
+
+        Ok(())
+    }
+
     /// Request VRF for crash point generation
     pub fn request_crash_vrf(ctx: Context<RequestCrashVrf>) -> Result<()> {
-        require!(!ctx.accounts.casino.is_paused, CasinoError::GamePaused);
+        ctx.accounts.casino.require_role(Role::Operator, &ctx.accounts.authority.key())?;
+        ctx.accounts.casino.require_not_paused(PAUSE_ROUNDS)?;
+
+        // Only one round may await randomness at a time
+        if ctx.accounts.casino.current_game_id > 0 {
+            let current_game = ctx.accounts.current_game
+                .as_ref()
+                .ok_or(CasinoError::RoundNotSettled)?;
+            require!(
+                current_game.status != GameStatus::AwaitingRandomness,
+                CasinoError::RoundNotSettled
+            );
+        }
 
         // Request VRF from Switchboard
         // Note: Full VRF integration requires Switchboard setup
@@ -95,6 +369,20 @@ pub mod solana_crash_game {
             .checked_add(1)
             .ok_or(CasinoError::MathOverflow)?;
 
+        // Pending bets of earlier rounds stay in the outstanding exposure and stakes
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.casino.round_started_at = now;
+        ctx.accounts.casino.current_round_voided = false;
+
+        let game = &mut ctx.accounts.game;
+        game.game_id = ctx.accounts.casino.current_game_id;
+        game.status = GameStatus::AwaitingRandomness;
+        game.request_slot = Clock::get()?.slot;
+        game.launched_at = now
+            .checked_add(ctx.accounts.casino.betting_window)
+            .ok_or(CasinoError::MathOverflow)?;
+        game.version = ACCOUNT_VERSION;
+
         emit!(VrfRequested {
             game_id: ctx.accounts.casino.current_game_id,
         });
@@ -104,480 +392,4230 @@ pub mod solana_crash_game {
 
     /// Resolve crash game with VRF result
     pub fn resolve_crash(ctx: Context<ResolveCrash>, vrf_result: [u8; 32]) -> Result<()> {
-        require!(!ctx.accounts.casino.is_paused, CasinoError::GamePaused);
+        ctx.accounts.casino.require_role(Role::Operator, &ctx.accounts.authority.key())?;
+        ctx.accounts.casino.require_not_paused(PAUSE_ROUNDS)?;
+
+        require!(
+            ctx.accounts.game.status == GameStatus::AwaitingRandomness && !ctx.accounts.game.is_resolved,
+            CasinoError::RoundNotAwaitingRandomness
+        );
 
         // Calculate crash multiplier from VRF
-        let crash_multiplier = calculate_crash_multiplier(vrf_result)?;
+        let crash_multiplier = calculate_crash_multiplier(vrf_result, ctx.accounts.casino.curve_scale)?;
 
         let game = &mut ctx.accounts.game;
         game.crash_multiplier = crash_multiplier;
         game.is_resolved = true;
+        game.status = GameStatus::Resolved;
 
         emit!(GameResolved {
             game_id: ctx.accounts.casino.current_game_id,
             crash_multiplier,
         });
 
+        // A rare crash point awards the whole jackpot pool to the round's bettors by stake
+        // Rounds without a paid stake left, such as free bets only, have nobody to share it
+        let casino = &mut ctx.accounts.casino;
+        let trigger = casino.jackpot_trigger_multiplier;
+        if trigger > 0
+            && crash_multiplier >= trigger
+            && casino.jackpot_pool > 0
+            && game.total_wagered > 0
+        {
+            game.jackpot_amount = casino.jackpot_pool;
+            casino.jackpot_pool = 0;
+
+            emit!(JackpotTriggered {
+                game_id: game.game_id,
+                crash_multiplier,
+                amount: game.jackpot_amount,
+                total_wagered: game.total_wagered,
+            });
+        }
+
         Ok(())
     }
 
-    /// Cash out before crash
+    /// Cash out of a round in flight at a multiplier it has already reached
+    /// The crash point is still hidden, `settle_bet` pays the bet only if the round crashes at or above it
     pub fn cashout(ctx: Context<Cashout>, multiplier_at_cashout: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_CASHOUTS)?;
+        let game = &mut ctx.accounts.game;
         let bet = &mut ctx.accounts.bet;
         require!(bet.status == BetStatus::Pending, CasinoError::InvalidBetStatus);
+        require!(bet.cashout_multiplier.is_none(), CasinoError::CashoutAlreadyLocked);
+        require!(game.status != GameStatus::Voided, CasinoError::RoundVoided);
+        require!(game.status == GameStatus::AwaitingRandomness, CasinoError::GameAlreadyCrashed);
+
+        // Only a multiplier the live round has climbed to can be taken
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= game.launched_at, CasinoError::RoundNotInFlight);
         require!(
-            multiplier_at_cashout <= ctx.accounts.game.crash_multiplier || !ctx.accounts.game.is_resolved,
-            CasinoError::GameAlreadyCrashed
+            multiplier_at_cashout <= calculate_live_multiplier(now - game.launched_at),
+            CasinoError::MultiplierNotReached
+        );
+        require!(
+            multiplier_at_cashout >= bet.min_cashout_multiplier,
+            CasinoError::FreeBetMultiplierTooLow
         );
 
-        // Calculate payout with house edge
-        let house_edge_bps = ctx.accounts.casino.house_edge_bps as u64;
-        let gross_payout = bet.amount
-            .checked_mul(multiplier_at_cashout)
-            .and_then(|x| x.checked_div(10000))
-            .ok_or(CasinoError::MathOverflow)?;
+        // Exposure was reserved up to the auto-cashout, which would have fired first anyway
+        if let Some(auto_cashout) = bet.auto_cashout_multiplier {
+            require!(multiplier_at_cashout <= auto_cashout, CasinoError::AboveAutoCashout);
+        }
 
-        let house_fee = gross_payout
-            .checked_mul(house_edge_bps)
-            .and_then(|x| x.checked_div(10000))
-            .ok_or(CasinoError::MathOverflow)?;
+        // The bet now pays at most the locked multiplier, release the rest of its exposure
+        let exposure = bet.exposure()?;
+        bet.cashout_multiplier = Some(multiplier_at_cashout);
+        let released = exposure.saturating_sub(bet.exposure()?);
+        game.exposure = game.exposure.saturating_sub(released);
+        ctx.accounts.casino.round_exposure = ctx.accounts.casino.round_exposure
+            .saturating_sub(released);
 
-        let net_payout = gross_payout
-            .checked_sub(house_fee)
-            .ok_or(CasinoError::MathOverflow)?;
+        emit!(CashoutLocked {
+            player: bet.player,
+            game_id: bet.game_id,
+            multiplier: multiplier_at_cashout,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a pending bet once its round is resolved, anyone may call
+    /// Bets whose locked cashout or auto-cashout was reached are paid at that multiplier, the rest are lost
+    pub fn settle_bet(ctx: Context<SettleBet>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_CASHOUTS)?;
+        let bet = &mut ctx.accounts.bet;
+        require!(bet.status == BetStatus::Pending, CasinoError::InvalidBetStatus);
+        require!(ctx.accounts.game.status == GameStatus::Resolved, CasinoError::GameNotResolved);
+
+        let clock = Clock::get()?;
+        let player_profile = &mut ctx.accounts.player_profile;
+        if player_profile.player == Pubkey::default() {
+            player_profile.player = bet.player;
+            player_profile.bump = ctx.bumps.player_profile;
+            player_profile.version = ACCOUNT_VERSION;
+        }
+        require!(
+            player_profile.referrer == Pubkey::default() || ctx.accounts.referrer_account.is_some(),
+            CasinoError::ReferrerAccountRequired
+        );
+
+        // Release the bet's exposure from its round and from the casino's outstanding total
+        let exposure = bet.exposure()?;
+        ctx.accounts.game.exposure = ctx.accounts.game.exposure.saturating_sub(exposure);
+        ctx.accounts.casino.round_exposure = ctx.accounts.casino.round_exposure
+            .saturating_sub(exposure);
+        ctx.accounts.casino.round_wagered = ctx.accounts.casino.round_wagered
+            .saturating_sub(bet.stake());
+
+        let crash_multiplier = ctx.accounts.game.crash_multiplier;
+        let house_edge_bps = ctx.accounts.casino.house_edge_bps;
+        let won_at = bet.exit_multiplier()
+            .filter(|multiplier| *multiplier <= crash_multiplier);
+        let (net_payout, house_fee) = match won_at {
+            Some(multiplier) => bet.payout_at(multiplier, house_edge_bps)?,
+            // The house edge on a lost stake is routed like a cashout fee
+            None => (0, bps_of(bet.stake(), house_edge_bps)?),
+        };
 
-        // Transfer payout
+        let casino_key = ctx.accounts.casino.key();
         let seeds = &[
-            b"vault",
-            &[ctx.accounts.casino.bump],
+            b"vault".as_ref(),
+            casino_key.as_ref(),
+            &[ctx.bumps.vault],
         ];
         let signer = &[&seeds[..]];
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.player_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, net_payout)?;
+        if let Some(multiplier) = won_at {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, net_payout)?;
 
-        // Update bet status
-        bet.status = BetStatus::CashedOut;
-        bet.cashout_multiplier = Some(multiplier_at_cashout);
+            bet.status = BetStatus::CashedOut;
+            bet.cashout_multiplier = Some(multiplier);
+            player_profile.record_payout(net_payout, multiplier, clock.slot);
+
+            let now = clock.unix_timestamp;
+            ctx.accounts.player_limits.credit_payout(net_payout, now);
+            let vault_balance = ctx.accounts.vault.amount;
+            if ctx.accounts.casino.record_house_flow(
+                -i64::try_from(net_payout).unwrap_or(i64::MAX),
+                vault_balance,
+                now,
+            ) {
+                emit!(CircuitBreakerTripped {
+                    net_flow: ctx.accounts.casino.breaker_net_flow,
+                    window_bankroll: ctx.accounts.casino.breaker_window_bankroll,
+                    threshold_bps: ctx.accounts.casino.breaker_threshold_bps,
+                    window_start: ctx.accounts.casino.breaker_window_start,
+                });
+            }
+        } else {
+            bet.status = BetStatus::Lost;
+            player_profile.last_active_slot = clock.slot;
+        }
 
-        // Update casino fees
         ctx.accounts.casino.total_fees = ctx.accounts.casino.total_fees
             .checked_add(house_fee)
             .ok_or(CasinoError::MathOverflow)?;
+        let rakeback = ctx.accounts.player_profile.accrue_rakeback(&mut ctx.accounts.casino, house_fee)?;
+        let split_fee = house_fee
+            .checked_sub(rakeback)
+            .ok_or(CasinoError::MathOverflow)?;
+        let referral_credit = match ctx.accounts.referrer_account.as_mut() {
+            Some(referrer_account) => referrer_account.accrue(&ctx.accounts.casino, split_fee)?,
+            None => 0,
+        };
+        let routed_fee = ctx.accounts.casino.route_fee(split_fee, referral_credit)?
+            .checked_add(rakeback)
+            .ok_or(CasinoError::MathOverflow)?;
 
-        emit!(Cashout {
-            player: bet.player,
-            game_id: bet.game_id,
-            multiplier: multiplier_at_cashout,
-            payout: net_payout,
-        });
+        if routed_fee > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, routed_fee)?;
+        }
+
+        match won_at {
+            Some(multiplier) => emit!(CashedOut {
+                player: bet.player,
+                game_id: bet.game_id,
+                multiplier,
+                payout: net_payout,
+            }),
+            None => emit!(BetLost {
+                player: bet.player,
+                game_id: bet.game_id,
+                amount: bet.amount,
+                crash_multiplier,
+            }),
+        }
 
         Ok(())
     }
 
-    /// Stake LP tokens
-    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
-        require!(!ctx.accounts.casino.is_paused, CasinoError::GamePaused);
-        require!(amount > 0, CasinoError::InvalidStakeAmount);
-
-        // Transfer LP tokens to staking vault
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.staker_lp_account.to_account_info(),
-            to: ctx.accounts.staking_vault.to_account_info(),
-            authority: ctx.accounts.staker.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+    /// Name the player's referrer, which can only be done once
+    pub fn register_referrer(ctx: Context<RegisterReferrer>, referrer: Pubkey) -> Result<()> {
+        let player = ctx.accounts.player.key();
+        require!(referrer != player && referrer != Pubkey::default(), CasinoError::InvalidReferrer);
 
-        // Update or create stake account
-        let stake = &mut ctx.accounts.stake;
-        if stake.amount == 0 {
-            stake.staker = ctx.accounts.staker.key();
-            stake.amount = amount;
-            stake.bump = ctx.bumps.stake;
-        } else {
-            stake.amount = stake.amount
-                .checked_add(amount)
-                .ok_or(CasinoError::MathOverflow)?;
+        let player_profile = &mut ctx.accounts.player_profile;
+        if player_profile.player == Pubkey::default() {
+            player_profile.player = player;
+            player_profile.bump = ctx.bumps.player_profile;
+            player_profile.version = ACCOUNT_VERSION;
         }
+        require!(player_profile.referrer == Pubkey::default(), CasinoError::ReferrerAlreadySet);
+        player_profile.referrer = referrer;
 
-        // Update total staked
-        ctx.accounts.casino.total_staked = ctx.accounts.casino.total_staked
-            .checked_add(amount)
+        let referrer_account = &mut ctx.accounts.referrer_account;
+        if referrer_account.referrer == Pubkey::default() {
+            referrer_account.referrer = referrer;
+            referrer_account.bump = ctx.bumps.referrer_account;
+            referrer_account.version = ACCOUNT_VERSION;
+        }
+        referrer_account.referred_count = referrer_account.referred_count
+            .checked_add(1)
             .ok_or(CasinoError::MathOverflow)?;
 
-        emit!(LpStaked {
-            staker: stake.staker,
-            amount: stake.amount,
+        emit!(ReferrerRegistered {
+            player,
+            referrer,
         });
 
         Ok(())
     }
 
-    /// Unstake LP tokens
-    pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
-        require!(!ctx.accounts.casino.is_paused, CasinoError::GamePaused);
-        require!(amount > 0, CasinoError::InvalidStakeAmount);
+    /// Claim fee shares accrued from referred players
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_CLAIMS)?;
+        let amount = ctx.accounts.referrer_account.pending_rewards;
+        require!(amount > 0, CasinoError::NoRewards);
 
-        let stake = &mut ctx.accounts.stake;
-        require!(stake.amount >= amount, CasinoError::InsufficientStake);
+        ctx.accounts.referrer_account.pending_rewards = 0;
+        ctx.accounts.casino.referral_pool = ctx.accounts.casino.referral_pool
+            .checked_sub(amount)
+            .ok_or(CasinoError::MathOverflow)?;
 
-        // Transfer LP tokens back
         let seeds = &[
-            b"staking_vault",
+            b"casino".as_ref(),
             &[ctx.accounts.casino.bump],
         ];
         let signer = &[&seeds[..]];
 
         let cpi_accounts = Transfer {
-            from: ctx.accounts.staking_vault.to_account_info(),
-            to: ctx.accounts.staker_lp_account.to_account_info(),
-            authority: ctx.accounts.staking_vault.to_account_info(),
+            from: ctx.accounts.fee_vault.to_account_info(),
+            to: ctx.accounts.referrer_token_account.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
         token::transfer(cpi_ctx, amount)?;
 
-        stake.amount = stake.amount
-            .checked_sub(amount)
-            .ok_or(CasinoError::MathOverflow)?;
-
-        ctx.accounts.casino.total_staked = ctx.accounts.casino.total_staked
-            .checked_sub(amount)
-            .ok_or(CasinoError::MathOverflow)?;
-
-        emit!(LpUnstaked {
-            staker: stake.staker,
+        emit!(ReferralRewardsClaimed {
+            referrer: ctx.accounts.referrer.key(),
             amount,
         });
 
         Ok(())
     }
 
-    /// Claim staking rewards
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
-        let stake = &ctx.accounts.stake;
-        require!(stake.amount > 0, CasinoError::NoStake);
+    /// Pay a bet its share of the jackpot its round won, anyone may call
+    pub fn settle_jackpot(ctx: Context<SettleJackpot>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_CLAIMS)?;
+        let bet = &mut ctx.accounts.bet;
+        let game = &ctx.accounts.game;
+        require!(game.jackpot_amount > 0, CasinoError::NoJackpot);
+        require!(!bet.jackpot_settled, CasinoError::JackpotAlreadySettled);
+        require!(bet.status != BetStatus::Refunded, CasinoError::InvalidBetStatus);
 
-        // Calculate proportional share of fees
-        let total_staked = ctx.accounts.casino.total_staked;
-        require!(total_staked > 0, CasinoError::NoStake);
+        let share = calculate_jackpot_share(game.jackpot_amount, bet.stake(), game.total_wagered)?;
+        require!(share > 0, CasinoError::NoJackpot);
+        bet.jackpot_settled = true;
 
-        let share = stake.amount
-            .checked_mul(ctx.accounts.casino.total_fees)
-            .and_then(|x| x.checked_div(total_staked))
-            .ok_or(CasinoError::MathOverflow)?;
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
 
-        let pending_rewards = share
-            .checked_sub(stake.claimed_rewards)
-            .ok_or(CasinoError::MathOverflow)?;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.fee_vault.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, share)?;
+
+        emit!(JackpotPaid {
+            player: bet.player,
+            game_id: bet.game_id,
+            amount: share,
+        });
+
+        Ok(())
+    }
+
+    /// Claim rakeback accrued on the player's bets
+    pub fn claim_rakeback(ctx: Context<ClaimRakeback>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_CLAIMS)?;
+        let amount = ctx.accounts.player_profile.rakeback_pending;
+        require!(amount > 0, CasinoError::NoRewards);
 
-        require!(pending_rewards > 0, CasinoError::NoRewards);
+        let player_profile = &mut ctx.accounts.player_profile;
+        player_profile.rakeback_pending = 0;
+        player_profile.rakeback_claimed = player_profile.rakeback_claimed
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        ctx.accounts.casino.rakeback_pool = ctx.accounts.casino.rakeback_pool
+            .checked_sub(amount)
+            .ok_or(CasinoError::MathOverflow)?;
 
-        // Transfer rewards from vault
         let seeds = &[
-            b"vault",
+            b"casino".as_ref(),
             &[ctx.accounts.casino.bump],
         ];
         let signer = &[&seeds[..]];
 
         let cpi_accounts = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.staker_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
+            from: ctx.accounts.fee_vault.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, pending_rewards)?;
+        token::transfer(cpi_ctx, amount)?;
 
-        stake.claimed_rewards = stake.claimed_rewards
-            .checked_add(pending_rewards)
-            .ok_or(CasinoError::MathOverflow)?;
+        emit!(RakebackClaimed {
+            player: ctx.accounts.player.key(),
+            amount,
+        });
 
-        emit!(RewardsClaimed {
-            staker: stake.staker,
-            amount: pending_rewards,
+        Ok(())
+    }
+
+    /// Set the player's own responsible-gambling limits
+    /// Tighter limits apply immediately, looser ones only after `LIMIT_LOOSEN_DELAY`
+    /// Self-exclusion is left as is, it is set through `self_exclude`
+    pub fn set_player_limits(ctx: Context<SetPlayerLimits>, limits: LimitSettings) -> Result<()> {
+        limits.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let player_limits = &mut ctx.accounts.player_limits;
+        if player_limits.player == Pubkey::default() {
+            player_limits.player = ctx.accounts.player.key();
+            player_limits.bump = ctx.bumps.player_limits;
+            player_limits.version = ACCOUNT_VERSION;
+        }
+        player_limits.refresh(now);
+
+        let limits = LimitSettings {
+            self_excluded_until: player_limits.limits.self_excluded_until,
+            ..limits
+        };
+
+        // Fields that tighten take effect now, the full request follows after the delay
+        let immediate = player_limits.limits.stricter(&limits);
+        player_limits.limits = immediate;
+        if immediate == limits {
+            player_limits.pending_limits = None;
+            player_limits.pending_effective_at = 0;
+        } else {
+            player_limits.pending_limits = Some(limits);
+            player_limits.pending_effective_at = now
+                .checked_add(LIMIT_LOOSEN_DELAY)
+                .ok_or(CasinoError::MathOverflow)?;
+        }
+
+        emit!(PlayerLimitsUpdated {
+            player: player_limits.player,
+            limits: player_limits.limits,
+            operator_limits: player_limits.operator_limits,
+            pending_limits: player_limits.pending_limits,
+            pending_effective_at: player_limits.pending_effective_at,
         });
 
         Ok(())
     }
 
-    /// Admin: Update house edge
-    pub fn update_house_edge(ctx: Context<UpdateConfig>, new_house_edge_bps: u16) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.casino.authority,
-            CasinoError::Unauthorized
-        );
-        ctx.accounts.casino.house_edge_bps = new_house_edge_bps;
+    /// Exclude the player from betting for `SELF_EXCLUSION_PERIOD`
+    /// The exclusion can be extended by excluding again but never shortened
+    pub fn self_exclude(ctx: Context<SetPlayerLimits>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let player_limits = &mut ctx.accounts.player_limits;
+        if player_limits.player == Pubkey::default() {
+            player_limits.player = ctx.accounts.player.key();
+            player_limits.bump = ctx.bumps.player_limits;
+            player_limits.version = ACCOUNT_VERSION;
+        }
+        player_limits.refresh(now);
+
+        let excluded_until = now
+            .checked_add(SELF_EXCLUSION_PERIOD)
+            .ok_or(CasinoError::MathOverflow)?;
+        player_limits.limits.self_excluded_until = player_limits.limits.self_excluded_until.max(excluded_until);
+
+        emit!(PlayerLimitsUpdated {
+            player: player_limits.player,
+            limits: player_limits.limits,
+            operator_limits: player_limits.operator_limits,
+            pending_limits: player_limits.pending_limits,
+            pending_effective_at: player_limits.pending_effective_at,
+        });
+
         Ok(())
     }
 
-    /// Admin: Pause/unpause game
-    pub fn set_pause(ctx: Context<UpdateConfig>, is_paused: bool) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.casino.authority,
-            CasinoError::Unauthorized
-        );
-        ctx.accounts.casino.is_paused = is_paused;
+    /// Operator: Impose limits on a player, the stricter of these and the player's own apply
+    pub fn set_operator_limits(ctx: Context<SetOperatorLimits>, limits: LimitSettings) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Operator, &ctx.accounts.authority.key())?;
+        limits.validate()?;
+
+        let player_limits = &mut ctx.accounts.player_limits;
+        if player_limits.player == Pubkey::default() {
+            player_limits.player = ctx.accounts.player.key();
+            player_limits.bump = ctx.bumps.player_limits;
+            player_limits.version = ACCOUNT_VERSION;
+        }
+        player_limits.operator_limits = limits;
+
+        emit!(PlayerLimitsUpdated {
+            player: player_limits.player,
+            limits: player_limits.limits,
+            operator_limits: player_limits.operator_limits,
+            pending_limits: player_limits.pending_limits,
+            pending_effective_at: player_limits.pending_effective_at,
+        });
+
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Casino::LEN,
-        seeds = [b"casino"],
-        bump
-    )]
-    pub casino: Account<'info, Casino>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// Stake LP tokens
+    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_STAKING)?;
+        require!(amount > 0, CasinoError::InvalidStakeAmount);
+        require!(ctx.accounts.stake.position_mint.is_none(), CasinoError::PositionTokenized);
 
-#[derive(Accounts)]
+        // Transfer LP tokens to staking vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.staker_lp_account.to_account_info(),
+            to: ctx.accounts.staking_vault.to_account_info(),
+            authority: ctx.accounts.staker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        // Update or create stake account
+        let now = Clock::get()?.unix_timestamp;
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        if stake.staker == Pubkey::default() {
+            stake.staker = ctx.accounts.staker.key();
+            stake.bump = ctx.bumps.stake;
+            stake.version = ACCOUNT_VERSION;
+        }
+        let streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+        stake.amount = stake.amount
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        stake.update_weight(casino, &streams, now)?;
+
+        // Update total staked
+        casino.total_staked = casino.total_staked
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(LpStaked {
+            staker: stake.staker,
+            amount: stake.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Unstake LP tokens immediately (only when no cooldown is configured)
+    pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_WITHDRAWALS)?;
+        require!(amount > 0, CasinoError::InvalidStakeAmount);
+        require!(
+            ctx.accounts.casino.unstake_cooldown == 0 || ctx.accounts.casino.is_shutdown,
+            CasinoError::UnstakeCooldownRequired
+        );
+
+        ctx.accounts.release_stake(amount, ctx.bumps.staking_vault, ctx.remaining_accounts)
+    }
+
+    /// Withdraw the whole stake at once, forfeiting every unclaimed and lock-boosted reward
+    /// Skips lock and cooldown only while withdrawals are paused or the casino is shut down
+    /// Unclaimed fees are forfeited to the pool and stream rewards to their streams
+    pub fn emergency_unstake(ctx: Context<UnstakeLp>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.stake.amount;
+        require!(amount > 0, CasinoError::NoStake);
+
+        // Outside an emergency the stake exits on the usual terms, only faster to claim nothing
+        let casino = &ctx.accounts.casino;
+        let stake = &ctx.accounts.stake;
+        let is_emergency = casino.pause_flags & PAUSE_WITHDRAWALS != 0 || casino.is_shutdown;
+        if !is_emergency {
+            require!(now >= stake.lock_end, CasinoError::StakeLocked);
+            require!(
+                casino.unstake_cooldown == 0
+                    || (stake.unstake_amount == amount && now >= stake.unstake_available_at),
+                CasinoError::UnstakeCooldownActive
+            );
+        }
+
+        ctx.accounts.transfer_lp_tokens(amount, ctx.bumps.staking_vault)?;
+
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        let mut streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+
+        // Unclaimed stream rewards return to their streams, credited to stakers on the next update
+        for ((stream, checkpoint), info) in streams
+            .iter_mut()
+            .zip(stake.stream_rewards.iter())
+            .zip(ctx.remaining_accounts.iter())
+        {
+            let index = stream.index as usize;
+            stream.undistributed = stream.undistributed
+                .checked_add(checkpoint.pending_rewards)
+                .and_then(|x| x.checked_add(stake.locked_stream_rewards[index]))
+                .ok_or(CasinoError::MathOverflow)?;
+            stream.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+
+        // The boost earned by a lock broken early is forfeited with the unclaimed rewards
+        let forfeited_rewards = stake.pending_rewards
+            .checked_add(stake.locked_rewards)
+            .ok_or(CasinoError::MathOverflow)?;
+        stake.pending_rewards = 0;
+        stake.locked_rewards = 0;
+        for checkpoint in stake.stream_rewards.iter_mut() {
+            checkpoint.pending_rewards = 0;
+        }
+        stake.locked_stream_rewards = [0; MAX_REWARD_STREAMS];
+
+        stake.amount = 0;
+        stake.unstake_amount = 0;
+        stake.unstake_available_at = 0;
+        stake.lock_end = 0;
+        stake.update_weight(casino, &streams, now)?;
+
+        casino.total_staked = casino.total_staked
+            .checked_sub(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        // Forfeited fees go to the remaining stakers, or the treasury if none are left
+        if forfeited_rewards > 0 {
+            if casino.total_weight > 0 {
+                casino.distribute_staker_fees(forfeited_rewards)?;
+            } else {
+                casino.staker_pool = casino.staker_pool
+                    .checked_sub(forfeited_rewards)
+                    .ok_or(CasinoError::MathOverflow)?;
+                casino.treasury_pool = casino.treasury_pool
+                    .checked_add(forfeited_rewards)
+                    .ok_or(CasinoError::MathOverflow)?;
+            }
+        }
+
+        emit!(EmergencyUnstaked {
+            staker: stake.staker,
+            amount,
+            forfeited_rewards,
+        });
+
+        Ok(())
+    }
+
+    /// Start the unstake cooldown for part of a stake
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_WITHDRAWALS)?;
+        require!(amount > 0, CasinoError::InvalidStakeAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let stake = &mut ctx.accounts.stake;
+        require!(now >= stake.lock_end, CasinoError::StakeLocked);
+
+        let unstake_amount = stake.unstake_amount
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        require!(stake.amount >= unstake_amount, CasinoError::InsufficientStake);
+
+        // Tokens keep backing the bankroll and earning fees until released
+        // Adding to a pending request restarts the timer
+        stake.unstake_amount = unstake_amount;
+        stake.unstake_available_at = now
+            .checked_add(ctx.accounts.casino.unstake_cooldown)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(UnstakeRequested {
+            staker: stake.staker,
+            amount: unstake_amount,
+            available_at: stake.unstake_available_at,
+        });
+
+        Ok(())
+    }
+
+    /// Release LP tokens whose unstake cooldown has ended
+    pub fn complete_unstake(ctx: Context<UnstakeLp>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_WITHDRAWALS)?;
+
+        let amount = ctx.accounts.stake.unstake_amount;
+        require!(amount > 0, CasinoError::NoUnstakeRequest);
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.stake.unstake_available_at,
+            CasinoError::UnstakeCooldownActive
+        );
+
+        ctx.accounts.stake.unstake_amount = 0;
+        ctx.accounts.stake.unstake_available_at = 0;
+
+        ctx.accounts.release_stake(amount, ctx.bumps.staking_vault, ctx.remaining_accounts)
+    }
+
+    /// Claim staking rewards from house fees and every reward stream
+    /// Remaining accounts: all reward streams, then a (stream vault, staker token account) pair per stream
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_CLAIMS)?;
+        let now = Clock::get()?.unix_timestamp;
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        require!(stake.amount > 0, CasinoError::NoStake);
+
+        // Settle rewards earned at the current weight, then let the boost decay
+        let streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+        stake.update_weight(casino, &streams, now)?;
+
+        let pending_rewards = stake.pending_rewards;
+        let has_stream_rewards = streams
+            .iter()
+            .any(|stream| stake.stream_rewards[stream.index as usize].pending_rewards > 0);
+        require!(pending_rewards > 0 || has_stream_rewards, CasinoError::NoRewards);
+
+        stake.pending_rewards = 0;
+        stake.claimed_rewards = stake.claimed_rewards
+            .checked_add(pending_rewards)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        casino.staker_pool = casino.staker_pool
+            .checked_sub(pending_rewards)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // Transfer fee rewards from fee vault
+        if pending_rewards > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.fee_vault.to_account_info(),
+                to: ctx.accounts.staker_token_account.to_account_info(),
+                authority: ctx.accounts.casino.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, pending_rewards)?;
+        }
+
+        // Transfer each stream's rewards from its vault
+        let payout_accounts = &ctx.remaining_accounts[streams.len()..];
+        require!(
+            payout_accounts.len() >= streams.len() * 2,
+            CasinoError::InvalidRewardStream
+        );
+
+        for stream in streams.iter() {
+            let index = stream.index as usize;
+            let amount = ctx.accounts.stake.stream_rewards[index].pending_rewards;
+            if amount == 0 {
+                continue;
+            }
+
+            let stream_vault = &payout_accounts[index * 2];
+            let staker_stream_account = &payout_accounts[index * 2 + 1];
+            require!(stream_vault.key() == stream.vault, CasinoError::InvalidRewardStream);
+
+            let cpi_accounts = Transfer {
+                from: stream_vault.clone(),
+                to: staker_stream_account.clone(),
+                authority: ctx.accounts.casino.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, amount)?;
+
+            ctx.accounts.stake.stream_rewards[index].pending_rewards = 0;
+
+            emit!(StreamRewardsClaimed {
+                staker: ctx.accounts.stake.staker,
+                reward_mint: stream.reward_mint,
+                amount,
+            });
+        }
+
+        if pending_rewards > 0 {
+            emit!(RewardsClaimed {
+                staker: ctx.accounts.stake.staker,
+                amount: pending_rewards,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Convert pending fee rewards into more stake
+    /// Callable by the staker, or by any keeper when the stake opted into auto-compounding
+    pub fn compound_rewards(ctx: Context<CompoundRewards>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_STAKING | PAUSE_CLAIMS)?;
+        require!(
+            ctx.accounts.stake.is_owner(&ctx.accounts.caller.key(), &ctx.accounts.position_token_account)
+                || ctx.accounts.stake.auto_compound,
+            CasinoError::AutoCompoundDisabled
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let streams = load_reward_streams(&ctx.accounts.casino, ctx.remaining_accounts, now)?;
+        ctx.accounts.stake.accrue_rewards(ctx.accounts.casino.acc_reward_per_weight, &streams, now)?;
+
+        let rewards = ctx.accounts.stake.pending_rewards;
+        require!(rewards > 0, CasinoError::NoRewards);
+
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // Keepers compounding on the staker's behalf take a cut when they pass an account for it
+        let is_keeper = !ctx.accounts.stake.is_owner(&ctx.accounts.caller.key(), &ctx.accounts.position_token_account);
+        let keeper_reward = match &ctx.accounts.keeper_token_account {
+            Some(keeper_token_account) if is_keeper => {
+                let keeper_reward = bps_of(rewards, ctx.accounts.casino.keeper_reward_bps)?;
+                if keeper_reward > 0 {
+                    let cpi_accounts = Transfer {
+                        from: ctx.accounts.fee_vault.to_account_info(),
+                        to: keeper_token_account.to_account_info(),
+                        authority: ctx.accounts.casino.to_account_info(),
+                    };
+                    let cpi_program = ctx.accounts.token_program.to_account_info();
+                    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+                    token::transfer(cpi_ctx, keeper_reward)?;
+                }
+                keeper_reward
+            }
+            _ => 0,
+        };
+        let compounded = rewards
+            .checked_sub(keeper_reward)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        let staked_amount = if ctx.accounts.staking_vault.mint == ctx.accounts.fee_vault.mint {
+            // Fees and stake share a mint, move the fees straight into the stake
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.fee_vault.to_account_info(),
+                to: ctx.accounts.staking_vault.to_account_info(),
+                authority: ctx.accounts.casino.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, compounded)?;
+
+            compounded
+        } else if ctx.accounts.staking_vault.mint == ctx.accounts.lp_mint.key() {
+            // Deposit the fees into the bankroll as new shares, on the same terms as an LP deposit
+            let (lp_amount, locked_lp) = ctx.accounts.casino.price_lp_deposit(
+                compounded,
+                ctx.accounts.vault.amount,
+                ctx.accounts.lp_mint.supply,
+            )?;
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.fee_vault.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.casino.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, compounded)?;
+
+            if locked_lp > 0 {
+                let cpi_accounts = MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.lp_escrow.to_account_info(),
+                    authority: ctx.accounts.casino.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+                token::mint_to(cpi_ctx, locked_lp)?;
+            }
+
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.staking_vault.to_account_info(),
+                authority: ctx.accounts.casino.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::mint_to(cpi_ctx, lp_amount)?;
+
+            lp_amount
+        } else {
+            return err!(CasinoError::CompoundUnavailable);
+        };
+
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        stake.pending_rewards = 0;
+        stake.claimed_rewards = stake.claimed_rewards
+            .checked_add(rewards)
+            .ok_or(CasinoError::MathOverflow)?;
+        stake.amount = stake.amount
+            .checked_add(staked_amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        stake.update_weight(casino, &streams, now)?;
+
+        casino.staker_pool = casino.staker_pool
+            .checked_sub(rewards)
+            .ok_or(CasinoError::MathOverflow)?;
+        casino.total_staked = casino.total_staked
+            .checked_add(staked_amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(RewardsCompounded {
+            staker: stake.staker,
+            caller: ctx.accounts.caller.key(),
+            rewards,
+            keeper_reward,
+            staked_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Opt a stake in or out of keeper-driven auto-compounding
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+        ctx.accounts.stake.auto_compound = auto_compound;
+        Ok(())
+    }
+
+    /// Lock an unlocked stake for a boosted reward weight (1 week to 1 year)
+    pub fn lock_stake(ctx: Context<UpdateLock>, lock_duration: i64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_STAKING)?;
+        require!(
+            (MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&lock_duration),
+            CasinoError::InvalidLockDuration
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        require!(stake.amount > 0, CasinoError::NoStake);
+        require!(stake.lock_end <= now, CasinoError::LockAlreadyActive);
+
+        let streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+        stake.lock_end = now
+            .checked_add(lock_duration)
+            .ok_or(CasinoError::MathOverflow)?;
+        stake.update_weight(casino, &streams, now)?;
+
+        emit!(StakeLockUpdated {
+            staker: stake.staker,
+            amount: stake.amount,
+            lock_end: stake.lock_end,
+            weight: stake.weight,
+        });
+
+        Ok(())
+    }
+
+    /// Extend an active lock to end `lock_duration` seconds from now
+    pub fn extend_lock(ctx: Context<UpdateLock>, lock_duration: i64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_STAKING)?;
+        require!(
+            (MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&lock_duration),
+            CasinoError::InvalidLockDuration
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        require!(stake.lock_end > now, CasinoError::NoActiveLock);
+
+        let lock_end = now
+            .checked_add(lock_duration)
+            .ok_or(CasinoError::MathOverflow)?;
+        require!(lock_end > stake.lock_end, CasinoError::InvalidLockDuration);
+
+        let streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+        stake.lock_end = lock_end;
+        stake.update_weight(casino, &streams, now)?;
+
+        emit!(StakeLockUpdated {
+            staker: stake.staker,
+            amount: stake.amount,
+            lock_end: stake.lock_end,
+            weight: stake.weight,
+        });
+
+        Ok(())
+    }
+
+    /// Add LP tokens to an active lock, the new tokens share its end time
+    pub fn increase_locked_amount(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_STAKING)?;
+        require!(amount > 0, CasinoError::InvalidStakeAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.stake.lock_end > now, CasinoError::NoActiveLock);
+        require!(ctx.accounts.stake.position_mint.is_none(), CasinoError::PositionTokenized);
+
+        // Transfer LP tokens to staking vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.staker_lp_account.to_account_info(),
+            to: ctx.accounts.staking_vault.to_account_info(),
+            authority: ctx.accounts.staker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+        let streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+        stake.amount = stake.amount
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        stake.update_weight(casino, &streams, now)?;
+
+        casino.total_staked = casino.total_staked
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(StakeLockUpdated {
+            staker: stake.staker,
+            amount: stake.amount,
+            lock_end: stake.lock_end,
+            weight: stake.weight,
+        });
+
+        Ok(())
+    }
+
+    /// Represent a stake as a transferable position token minted to the staker
+    /// Whoever holds the token controls the stake, its lock and its rewards
+    pub fn tokenize_stake(ctx: Context<TokenizeStake>) -> Result<()> {
+        require!(ctx.accounts.stake.position_mint.is_none(), CasinoError::PositionTokenized);
+        require!(ctx.accounts.stake.amount > 0, CasinoError::NoStake);
+
+        // Mint the single position token, the casino PDA is the mint authority
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.position_mint.to_account_info(),
+            to: ctx.accounts.staker_position_account.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::mint_to(cpi_ctx, 1)?;
+
+        let stake = &mut ctx.accounts.stake;
+        stake.position_mint = Some(ctx.accounts.position_mint.key());
+
+        emit!(StakeTokenized {
+            stake: stake.key(),
+            staker: stake.staker,
+            position_mint: ctx.accounts.position_mint.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Recompute a stake's decaying boost (permissionless)
+    pub fn refresh_stake(ctx: Context<RefreshStake>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let casino = &mut ctx.accounts.casino;
+        let stake = &mut ctx.accounts.stake;
+
+        let streams = load_reward_streams(casino, ctx.remaining_accounts, now)?;
+        stake.accrue_rewards(casino.acc_reward_per_weight, &streams, now)?;
+        stake.update_weight(casino, &streams, now)?;
+
+        Ok(())
+    }
+
+    /// Deposit bet tokens into the bankroll vault and mint LP shares at current NAV
+    pub fn deposit_liquidity(ctx: Context<DepositLiquidity>, amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_STAKING)?;
+        require!(amount > 0, CasinoError::InvalidLiquidityAmount);
+
+        let (lp_amount, locked_lp) = ctx.accounts.casino.price_lp_deposit(
+            amount,
+            ctx.accounts.vault.amount,
+            ctx.accounts.lp_mint.supply,
+        )?;
+
+        // Transfer bet tokens to vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.provider_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        // Mint LP tokens, the casino PDA is the mint authority
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        if locked_lp > 0 {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.lp_escrow.to_account_info(),
+                authority: ctx.accounts.casino.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::mint_to(cpi_ctx, locked_lp)?;
+        }
+
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.provider_lp_account.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::mint_to(cpi_ctx, lp_amount)?;
+
+        emit!(LiquidityDeposited {
+            provider: ctx.accounts.provider.key(),
+            amount,
+            lp_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Burn LP shares for bet tokens from the bankroll vault at current NAV
+    pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, lp_amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_WITHDRAWALS)?;
+        require!(lp_amount > 0, CasinoError::InvalidLiquidityAmount);
+
+        let bankroll = ctx.accounts.casino.bankroll_balance(ctx.accounts.vault.amount);
+        let amount = calculate_withdraw_amount(
+            lp_amount,
+            bankroll,
+            ctx.accounts.lp_mint.supply,
+        )?;
+        require!(amount > 0, CasinoError::InvalidLiquidityAmount);
+
+        // Instant withdrawals cannot touch funds backing pending bets
+        let free_balance = calculate_free_balance(
+            bankroll,
+            ctx.accounts.casino.round_exposure,
+        );
+        require!(amount <= free_balance, CasinoError::InsufficientFreeBalance);
+
+        // Burn LP tokens from provider
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.provider_lp_account.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::burn(cpi_ctx, lp_amount)?;
+
+        // Transfer underlying from vault
+        let casino_key = ctx.accounts.casino.key();
+        let seeds = &[
+            b"vault".as_ref(),
+            casino_key.as_ref(),
+            &[ctx.bumps.vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(LiquidityWithdrawn {
+            provider: ctx.accounts.provider.key(),
+            lp_amount,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Queue an LP withdrawal to be paid out once the current round settles
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, lp_amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_WITHDRAWALS)?;
+        require!(lp_amount > 0, CasinoError::InvalidLiquidityAmount);
+
+        // Escrow LP tokens so the shares stay priced against the bankroll
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.provider_lp_account.to_account_info(),
+            to: ctx.accounts.lp_escrow.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, lp_amount)?;
+
+        let request = &mut ctx.accounts.withdraw_request;
+        request.owner = ctx.accounts.provider.key();
+        request.lp_amount = lp_amount;
+        request.game_id = ctx.accounts.casino.current_game_id;
+        request.requested_at = Clock::get()?.unix_timestamp;
+        request.bump = ctx.bumps.withdraw_request;
+        request.version = ACCOUNT_VERSION;
+
+        emit!(WithdrawalRequested {
+            owner: request.owner,
+            lp_amount,
+            game_id: request.game_id,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out a queued LP withdrawal after its round has settled (permissionless)
+    pub fn process_withdrawal(ctx: Context<ProcessWithdrawal>) -> Result<()> {
+        ctx.accounts.casino.require_not_paused(PAUSE_WITHDRAWALS)?;
+
+        // No round exists before the first randomness request, so there is nothing to wait for
+        let settled = match ctx.accounts.game.as_ref() {
+            Some(game) => game.is_resolved || game.status == GameStatus::Voided,
+            None => ctx.accounts.withdraw_request.game_id == 0,
+        };
+        require!(settled, CasinoError::RoundNotSettled);
+
+        let lp_amount = ctx.accounts.withdraw_request.lp_amount;
+        let bankroll = ctx.accounts.casino.bankroll_balance(ctx.accounts.vault.amount);
+        let amount = calculate_withdraw_amount(
+            lp_amount,
+            bankroll,
+            ctx.accounts.lp_mint.supply,
+        )?;
+
+        // Pending bets of any round may still pay out, keep their exposure covered
+        let free_balance = calculate_free_balance(
+            bankroll,
+            ctx.accounts.casino.round_exposure,
+        );
+        require!(amount <= free_balance, CasinoError::InsufficientFreeBalance);
+
+        // Burn escrowed LP tokens
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.lp_escrow.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::burn(cpi_ctx, lp_amount)?;
+
+        // Transfer underlying from vault
+        let casino_key = ctx.accounts.casino.key();
+        let seeds = &[
+            b"vault".as_ref(),
+            casino_key.as_ref(),
+            &[ctx.bumps.vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(WithdrawalProcessed {
+            owner: ctx.accounts.withdraw_request.owner,
+            lp_amount,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a queued LP withdrawal and return the escrowed LP tokens
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        let lp_amount = ctx.accounts.withdraw_request.lp_amount;
+
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.lp_escrow.to_account_info(),
+            to: ctx.accounts.provider_lp_account.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, lp_amount)?;
+
+        emit!(WithdrawalCancelled {
+            owner: ctx.accounts.provider.key(),
+            lp_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Create the LP share mint for the bankroll vault
+    pub fn initialize_lp_mint(ctx: Context<InitializeLpMint>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        msg!("LP mint initialized: {}", ctx.accounts.lp_mint.key());
+        Ok(())
+    }
+
+    /// Treasurer: Withdraw operating revenue from the treasury bucket, capped per epoch
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Treasurer, &ctx.accounts.treasurer.key())?;
+        require!(amount > 0, CasinoError::InvalidWithdrawAmount);
+        require!(
+            amount <= ctx.accounts.casino.treasury_pool,
+            CasinoError::InsufficientTreasury
+        );
+
+        let clock = Clock::get()?;
+        let casino = &mut ctx.accounts.casino;
+        let allowance = calculate_epoch_allowance(
+            casino.treasury_epoch_limit,
+            casino.treasury_withdrawn_in_epoch,
+            casino.treasury_epoch,
+            clock.epoch,
+        );
+        require!(amount <= allowance, CasinoError::TreasuryLimitExceeded);
+
+        if casino.treasury_epoch != clock.epoch {
+            casino.treasury_epoch = clock.epoch;
+            casino.treasury_withdrawn_in_epoch = 0;
+        }
+        casino.treasury_withdrawn_in_epoch = casino.treasury_withdrawn_in_epoch
+            .checked_add(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        casino.treasury_pool = casino.treasury_pool
+            .checked_sub(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        // Record the withdrawal in the on-chain log
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        withdrawal.index = casino.treasury_withdrawal_count;
+        withdrawal.treasurer = ctx.accounts.treasurer.key();
+        withdrawal.destination = ctx.accounts.destination.key();
+        withdrawal.amount = amount;
+        withdrawal.epoch = clock.epoch;
+        withdrawal.timestamp = clock.unix_timestamp;
+        withdrawal.bump = ctx.bumps.withdrawal;
+        withdrawal.version = ACCOUNT_VERSION;
+
+        casino.treasury_withdrawal_count = casino.treasury_withdrawal_count
+            .checked_add(1)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        // Transfer from fee vault
+        let seeds = &[
+            b"casino".as_ref(),
+            &[ctx.accounts.casino.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.fee_vault.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority: ctx.accounts.casino.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(TreasuryWithdrawn {
+            index: ctx.accounts.withdrawal.index,
+            treasurer: ctx.accounts.treasurer.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            epoch: clock.epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Update the maximum treasury withdrawal per epoch
+    pub fn set_treasury_epoch_limit(ctx: Context<UpdateConfig>, treasury_epoch_limit: u64) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        ctx.accounts.casino.treasury_epoch_limit = treasury_epoch_limit;
+        Ok(())
+    }
+
+    /// Admin: Register an extra reward mint emitted to stakers by weight until `end_time`
+    /// The stream vault must be funded separately to cover the emissions
+    pub fn register_reward_stream(
+        ctx: Context<RegisterRewardStream>,
+        emission_rate: u64,
+        end_time: i64,
+    ) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!(
+            (ctx.accounts.casino.reward_stream_count as usize) < MAX_REWARD_STREAMS,
+            CasinoError::TooManyRewardStreams
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(emission_rate > 0 && end_time > now, CasinoError::InvalidRewardStream);
+
+        let stream = &mut ctx.accounts.reward_stream;
+        stream.casino = ctx.accounts.casino.key();
+        stream.index = ctx.accounts.casino.reward_stream_count;
+        stream.reward_mint = ctx.accounts.reward_mint.key();
+        stream.vault = ctx.accounts.stream_vault.key();
+        stream.emission_rate = emission_rate;
+        stream.end_time = end_time;
+        stream.last_update = now;
+        stream.acc_reward_per_weight = 0;
+        stream.bump = ctx.bumps.reward_stream;
+        stream.version = ACCOUNT_VERSION;
+
+        ctx.accounts.casino.reward_stream_count = ctx.accounts.casino.reward_stream_count
+            .checked_add(1)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(RewardStreamRegistered {
+            index: stream.index,
+            reward_mint: stream.reward_mint,
+            emission_rate,
+            end_time,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Create the fee vault that holds routed house fees
+    pub fn initialize_fee_vault(ctx: Context<InitializeFeeVault>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        msg!("Fee vault initialized: {}", ctx.accounts.fee_vault.key());
+        Ok(())
+    }
+
+    /// Admin: Create the bankroll vault holding the bet token, it signs for itself with its canonical bump
+    pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        msg!("Bankroll vault initialized: {}", ctx.accounts.vault.key());
+        Ok(())
+    }
+
+    /// Admin: Create the staking vault for the token stakers lock, bankroll shares or an external LP token
+    pub fn initialize_staking_vault(ctx: Context<InitializeStakingVault>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        msg!("Staking vault initialized: {}", ctx.accounts.staking_vault.key());
+        Ok(())
+    }
+
+    /// Admin: Queue a change to house edge, bet limits, fee split or crash curve
+    /// The change can be executed once `CONFIG_CHANGE_DELAY` has passed
+    pub fn queue_config_change(ctx: Context<QueueConfigChange>, params: ConfigParams) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!(!params.is_empty(), CasinoError::EmptyConfigChange);
+        params.validate(&ctx.accounts.casino.config())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let eta = now
+            .checked_add(CONFIG_CHANGE_DELAY)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.casino = ctx.accounts.casino.key();
+        pending_change.params = params;
+        pending_change.queued_at = now;
+        pending_change.eta = eta;
+        pending_change.bump = ctx.bumps.pending_change;
+        pending_change.version = ACCOUNT_VERSION;
+
+        emit!(ConfigChangeQueued {
+            params,
+            eta,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Apply a queued config change after its delay has passed
+    pub fn execute_config_change(ctx: Context<FinalizeConfigChange>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= ctx.accounts.pending_change.eta,
+            CasinoError::ConfigChangeNotReady
+        );
+
+        // Bounds are checked again since other settings may have moved since queueing
+        let params = ctx.accounts.pending_change.params;
+        let before = ctx.accounts.casino.apply_config(&params)?;
+
+        emit!(ConfigUpdated {
+            before,
+            after: params,
+        });
+
+        emit!(ConfigChangeExecuted {
+            params,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Drop a queued config change
+    pub fn cancel_config_change(ctx: Context<FinalizeConfigChange>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        emit!(ConfigChangeCancelled {
+            params: ctx.accounts.pending_change.params,
+        });
+
+        Ok(())
+    }
+
+    /// Risk manager: Update risk settings that apply immediately
+    /// House edge, bet limits, fee split and curve changes must go through `queue_config_change`
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::RiskManager, &ctx.accounts.authority.key())?;
+        require!(!params.is_empty(), CasinoError::EmptyConfigChange);
+        require!(!params.is_timelocked(), CasinoError::ConfigTimelocked);
+
+        let before = ctx.accounts.casino.apply_config(&params)?;
+
+        emit!(ConfigUpdated {
+            before,
+            after: params,
+        });
+
+        Ok(())
+    }
+
+    /// Risk manager: Update how many slots a round waits for randomness before it can be voided
+    pub fn set_vrf_timeout(ctx: Context<UpdateConfig>, vrf_timeout_slots: u64) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::RiskManager, &ctx.accounts.authority.key())?;
+        require!(
+            (MIN_VRF_TIMEOUT_SLOTS..=MAX_VRF_TIMEOUT_SLOTS).contains(&vrf_timeout_slots),
+            CasinoError::InvalidVrfTimeout
+        );
+        ctx.accounts.casino.vrf_timeout_slots = vrf_timeout_slots;
+        Ok(())
+    }
+
+    /// Record the compliance signer's attestation that a player may bet until `expiry`
+    /// The signature is checked by the ed25519 program instruction right before this one
+    pub fn submit_attestation(ctx: Context<SubmitAttestation>, expiry: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(expiry > now, CasinoError::AttestationExpired);
+
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current_index = load_current_index_checked(&instructions)?;
+        require!(current_index > 0, CasinoError::InvalidAttestation);
+        let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, &instructions)?;
+        require!(ed25519_ix.program_id == ed25519_program::ID, CasinoError::InvalidAttestation);
+
+        let (signer, message) = parse_ed25519_instruction(&ed25519_ix.data)?;
+        let compliance_signer = ctx.accounts.casino.compliance_signer;
+        require!(
+            compliance_signer != Pubkey::default() && signer == compliance_signer,
+            CasinoError::InvalidAttestation
+        );
+        let player = ctx.accounts.player.key();
+        require!(
+            message == attestation_message(&player, expiry),
+            CasinoError::InvalidAttestation
+        );
+
+        // A fresh attestation replaces the previous one, the account is only set up once
+        let attestation = &mut ctx.accounts.attestation;
+        if attestation.player == Pubkey::default() {
+            attestation.player = player;
+            attestation.bump = ctx.bumps.attestation;
+            attestation.version = ACCOUNT_VERSION;
+        }
+        attestation.signer = signer;
+        attestation.expiry = expiry;
+
+        emit!(AttestationSubmitted {
+            player,
+            signer,
+            expiry,
+        });
+
+        Ok(())
+    }
+
+    /// Risk manager: Update the loss share and window that trip the circuit breaker
+    pub fn set_circuit_breaker(
+        ctx: Context<UpdateConfig>,
+        breaker_threshold_bps: u16,
+        breaker_window: i64,
+    ) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::RiskManager, &ctx.accounts.authority.key())?;
+        require!(
+            breaker_threshold_bps <= 10000
+                && (MIN_BREAKER_WINDOW..=MAX_BREAKER_WINDOW).contains(&breaker_window),
+            CasinoError::InvalidCircuitBreaker
+        );
+
+        let casino = &mut ctx.accounts.casino;
+        casino.breaker_threshold_bps = breaker_threshold_bps;
+        casino.breaker_window = breaker_window;
+        Ok(())
+    }
+
+    /// Admin: Set each referral tier's share of referred players' house fees
+    pub fn set_referral_rates(
+        ctx: Context<UpdateConfig>,
+        referral_rates_bps: [u16; REFERRAL_TIER_COUNT],
+    ) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!(
+            referral_rates_bps.iter().all(|rate| *rate <= 10000),
+            CasinoError::InvalidReferralRate
+        );
+        ctx.accounts.casino.referral_rates_bps = referral_rates_bps;
+        Ok(())
+    }
+
+    /// Admin: Set the VIP tiers, players move tier as they next bet
+    pub fn set_vip_tiers(ctx: Context<UpdateConfig>, vip_tiers: [VipTier; VIP_TIER_COUNT]) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        validate_vip_tiers(&vip_tiers, ctx.accounts.casino.max_payout)?;
+        ctx.accounts.casino.vip_tiers = vip_tiers;
+        Ok(())
+    }
+
+    /// Admin: Move a referrer to another referral tier
+    pub fn set_referrer_tier(ctx: Context<SetReferrerTier>, tier: u8) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!((tier as usize) < REFERRAL_TIER_COUNT, CasinoError::InvalidReferralTier);
+        ctx.accounts.referrer_account.tier = tier;
+        Ok(())
+    }
+
+    /// Admin: Set the compliance signer and whether bets require its attestation
+    pub fn set_gated_mode(ctx: Context<UpdateConfig>, compliance_signer: Pubkey, gated: bool) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!(
+            !gated || compliance_signer != Pubkey::default(),
+            CasinoError::InvalidComplianceSigner
+        );
+
+        let casino = &mut ctx.accounts.casino;
+        casino.compliance_signer = compliance_signer;
+        casino.gated = gated;
+        Ok(())
+    }
+
+    /// Risk manager: Set the crash multiplier that awards the jackpot, 0 disables it
+    pub fn set_jackpot_trigger(ctx: Context<UpdateConfig>, jackpot_trigger_multiplier: u64) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::RiskManager, &ctx.accounts.authority.key())?;
+        require!(
+            jackpot_trigger_multiplier == 0
+                || (jackpot_trigger_multiplier > MIN_CRASH_MULTIPLIER
+                    && jackpot_trigger_multiplier <= MAX_CRASH_MULTIPLIER),
+            CasinoError::InvalidJackpotTrigger
+        );
+        ctx.accounts.casino.jackpot_trigger_multiplier = jackpot_trigger_multiplier;
+        Ok(())
+    }
+
+    /// Risk manager: Update unstake cooldown (seconds, 0 allows instant unstaking)
+    pub fn set_unstake_cooldown(ctx: Context<UpdateConfig>, unstake_cooldown: i64) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::RiskManager, &ctx.accounts.authority.key())?;
+        require!(unstake_cooldown >= 0, CasinoError::InvalidCooldown);
+        ctx.accounts.casino.unstake_cooldown = unstake_cooldown;
+        Ok(())
+    }
+
+    /// Guardian or admin: Pause operations by PAUSE_* flags, already paused ones stay paused
+    pub fn pause(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+        let signer = ctx.accounts.authority.key();
+        require!(
+            ctx.accounts.casino.has_role(Role::Guardian, &signer)
+                || ctx.accounts.casino.has_role(Role::Admin, &signer),
+            CasinoError::Unauthorized
+        );
+        require!(!ctx.accounts.casino.is_shutdown, CasinoError::CasinoShutdown);
+        require!(flags != 0 && flags & !PAUSE_ALL == 0, CasinoError::InvalidPauseFlags);
+
+        let casino = &mut ctx.accounts.casino;
+        casino.pause_flags |= flags;
+
+        emit!(PauseFlagsUpdated {
+            pause_flags: casino.pause_flags,
+            updated_by: signer,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Resume operations by PAUSE_* flags
+    pub fn unpause(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!(!ctx.accounts.casino.is_shutdown, CasinoError::CasinoShutdown);
+        require!(flags != 0 && flags & !PAUSE_ALL == 0, CasinoError::InvalidPauseFlags);
+
+        let casino = &mut ctx.accounts.casino;
+        casino.pause_flags &= !flags;
+
+        // Resuming bets starts a fresh circuit breaker window
+        if flags & PAUSE_BETS != 0 {
+            casino.breaker_window_start = 0;
+        }
+
+        emit!(PauseFlagsUpdated {
+            pause_flags: casino.pause_flags,
+            updated_by: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Permanently shut the casino down and cancel every pending bet
+    /// Pending bets of any round are refunded through `refund_bet`, LPs and stakers exit pro-rata
+    pub fn shutdown(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        require!(!ctx.accounts.casino.is_shutdown, CasinoError::CasinoShutdown);
+
+        let casino = &mut ctx.accounts.casino;
+        casino.is_shutdown = true;
+        casino.shutdown_game_id = casino.current_game_id;
+
+        // Stakes of all pending bets become a refund owed ahead of LPs, their payouts are void
+        casino.refund_liability = casino.refund_liability
+            .checked_add(casino.round_wagered)
+            .ok_or(CasinoError::MathOverflow)?;
+        casino.round_wagered = 0;
+        casino.round_exposure = 0;
+
+        // Pause flags are frozen from here, exits and claims stay open
+        casino.pause_flags = PAUSE_BETS | PAUSE_ROUNDS | PAUSE_CASHOUTS | PAUSE_STAKING;
+
+        emit!(CasinoShutdown {
+            game_id: casino.shutdown_game_id,
+            refund_liability: casino.refund_liability,
+        });
+
+        Ok(())
+    }
+
+    /// Return the stake of a pending bet to its player once the casino is shut down or its round voided
+    /// Permissionless, the refund can only go to the player's own token account
+    pub fn refund_bet(ctx: Context<RefundBet>) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        require!(bet.status == BetStatus::Pending, CasinoError::InvalidBetStatus);
+        // Cashouts are frozen by shutdown, so pending bets of any round can only be refunded
+        let cancelled_by_shutdown = ctx.accounts.casino.is_shutdown;
+        let voided = ctx.accounts.game.status == GameStatus::Voided;
+        require!(cancelled_by_shutdown || voided, CasinoError::BetNotRefundable);
+
+        let casino_key = ctx.accounts.casino.key();
+        let seeds = &[
+            b"vault".as_ref(),
+            casino_key.as_ref(),
+            &[ctx.bumps.vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        // Free bets are cancelled without a transfer, their voucher stays spent
+        let stake = bet.stake();
+        if stake > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, stake)?;
+
+            let clock = Clock::get()?;
+            if let Some(player_profile) = ctx.accounts.player_profile.as_mut() {
+                player_profile.record_refund(stake, clock.slot);
+            }
+            ctx.accounts.player_limits.reverse_wager(stake, clock.unix_timestamp);
+        }
+
+        bet.status = BetStatus::Refunded;
+        ctx.accounts.casino.refund_liability = ctx.accounts.casino.refund_liability
+            .saturating_sub(stake);
+
+        // The refunded stake leaves its round, any jackpot share it had won goes back to the pool
+        let game = &mut ctx.accounts.game;
+        let jackpot_share = calculate_jackpot_share(game.jackpot_amount, stake, game.total_wagered)?;
+        game.jackpot_amount = game.jackpot_amount
+            .checked_sub(jackpot_share)
+            .ok_or(CasinoError::MathOverflow)?;
+        game.total_wagered = game.total_wagered.saturating_sub(stake);
+        ctx.accounts.casino.jackpot_pool = ctx.accounts.casino.jackpot_pool
+            .checked_add(jackpot_share)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(BetRefunded {
+            player: bet.player,
+            game_id: bet.game_id,
+            amount: stake,
+        });
+
+        Ok(())
+    }
+
+    /// Void a round whose randomness never arrived within `vrf_timeout_slots`
+    /// Permissionless, its pending bets are then refunded through `refund_bet`
+    pub fn void_round(ctx: Context<VoidRound>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        require!(
+            game.status == GameStatus::AwaitingRandomness && !game.is_resolved,
+            CasinoError::RoundNotAwaitingRandomness
+        );
+
+        let current_slot = Clock::get()?.slot;
+        require!(
+            is_randomness_expired(game.request_slot, ctx.accounts.casino.vrf_timeout_slots, current_slot),
+            CasinoError::RandomnessNotExpired
+        );
+
+        game.status = GameStatus::Voided;
+
+        // Stakes of the voided round are owed back ahead of LPs, shutdown has already counted them
+        let casino = &mut ctx.accounts.casino;
+        if !casino.is_shutdown {
+            casino.refund_liability = casino.refund_liability
+                .checked_add(game.total_wagered)
+                .ok_or(CasinoError::MathOverflow)?;
+            casino.round_wagered = casino.round_wagered.saturating_sub(game.total_wagered);
+        }
+        casino.round_exposure = casino.round_exposure.saturating_sub(game.exposure);
+        game.exposure = 0;
+        if game.game_id == casino.current_game_id {
+            casino.current_round_voided = true;
+        }
+
+        emit!(RoundVoided {
+            game_id: game.game_id,
+            request_slot: game.request_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Propose a new admin, who must call `accept_authority` to take over
+    pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+        ctx.accounts.casino.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposed {
+            authority: ctx.accounts.casino.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a pending admin transfer, signed by the proposed authority
+    pub fn accept_authority(ctx: Context<UpdateConfig>) -> Result<()> {
+        let casino = &mut ctx.accounts.casino;
+        require!(
+            casino.pending_authority == Some(ctx.accounts.authority.key()),
+            CasinoError::NoPendingAuthority
+        );
+
+        let previous_authority = casino.authority;
+        casino.authority = ctx.accounts.authority.key();
+        casino.pending_authority = None;
+
+        emit!(AuthorityTransferred {
+            previous_authority,
+            new_authority: casino.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Assign an operational role to a new key
+    pub fn set_role(ctx: Context<UpdateConfig>, role: Role, holder: Pubkey) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        let casino = &mut ctx.accounts.casino;
+        let slot = match role {
+            Role::Admin => return err!(CasinoError::InvalidRole),
+            Role::Operator => &mut casino.operator,
+            Role::Guardian => &mut casino.guardian,
+            Role::Treasurer => &mut casino.treasurer,
+            Role::RiskManager => &mut casino.risk_manager,
+            Role::Promo => &mut casino.promo,
+        };
+        let previous_holder = *slot;
+        *slot = holder;
+
+        emit!(RoleUpdated {
+            role,
+            previous_holder,
+            new_holder: holder,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Upgrade the casino account to the current layout, growing it if needed
+    pub fn migrate_casino(ctx: Context<MigrateCasino>) -> Result<()> {
+        // The casino may not deserialize yet, authority is the first field in every layout
+        let authority = {
+            let data = ctx.accounts.casino.try_borrow_data()?;
+            data.get(8..40)
+                .and_then(|bytes| Pubkey::try_from(bytes).ok())
+                .ok_or(CasinoError::InvalidMigration)?
+        };
+        require!(authority == ctx.accounts.authority.key(), CasinoError::Unauthorized);
+
+        upgrade_account::<Casino>(
+            &ctx.accounts.casino.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + Casino::LEN,
+        )?;
+
+        emit!(AccountMigrated {
+            account: ctx.accounts.casino.key(),
+            version: ACCOUNT_VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: Upgrade any other program account to the current layout, growing it if needed
+    /// Stakes from before boosted weights earn nothing until `refresh_stake` is called
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        ctx.accounts.casino.require_role(Role::Admin, &ctx.accounts.authority.key())?;
+
+        let target = ctx.accounts.target.to_account_info();
+        let payer = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        let discriminator: [u8; 8] = target.try_borrow_data()?
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(CasinoError::InvalidMigration)?;
+
+        if discriminator == Bet::DISCRIMINATOR {
+            upgrade_account::<Bet>(&target, &payer, &system_program, 8 + Bet::LEN)?;
+        } else if discriminator == Game::DISCRIMINATOR {
+            upgrade_account::<Game>(&target, &payer, &system_program, 8 + Game::LEN)?;
+        } else if discriminator == Stake::DISCRIMINATOR {
+            upgrade_account::<Stake>(&target, &payer, &system_program, 8 + Stake::LEN)?;
+        } else if discriminator == WithdrawRequest::DISCRIMINATOR {
+            upgrade_account::<WithdrawRequest>(&target, &payer, &system_program, 8 + WithdrawRequest::LEN)?;
+        } else if discriminator == PlayerLimits::DISCRIMINATOR {
+            upgrade_account::<PlayerLimits>(&target, &payer, &system_program, 8 + PlayerLimits::LEN)?;
+        } else if discriminator == PlayerProfile::DISCRIMINATOR {
+            upgrade_account::<PlayerProfile>(&target, &payer, &system_program, 8 + PlayerProfile::LEN)?;
+        } else if discriminator == FreeBet::DISCRIMINATOR {
+            upgrade_account::<FreeBet>(&target, &payer, &system_program, 8 + FreeBet::LEN)?;
+        } else if discriminator == Referrer::DISCRIMINATOR {
+            upgrade_account::<Referrer>(&target, &payer, &system_program, 8 + Referrer::LEN)?;
+        } else if discriminator == PlayerAttestation::DISCRIMINATOR {
+            upgrade_account::<PlayerAttestation>(&target, &payer, &system_program, 8 + PlayerAttestation::LEN)?;
+        } else if discriminator == PendingConfigChange::DISCRIMINATOR {
+            upgrade_account::<PendingConfigChange>(&target, &payer, &system_program, 8 + PendingConfigChange::LEN)?;
+        } else if discriminator == TreasuryWithdrawal::DISCRIMINATOR {
+            upgrade_account::<TreasuryWithdrawal>(&target, &payer, &system_program, 8 + TreasuryWithdrawal::LEN)?;
+        } else if discriminator == RewardStream::DISCRIMINATOR {
+            upgrade_account::<RewardStream>(&target, &payer, &system_program, 8 + RewardStream::LEN)?;
+        } else {
+            return err!(CasinoError::InvalidMigration);
+        }
+
+        emit!(AccountMigrated {
+            account: target.key(),
+            version: ACCOUNT_VERSION,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Casino::LEN,
+        seeds = [b"casino"],
+        bump
+    )]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
 pub struct PlaceBet<'info> {
     #[account(mut)]
     pub casino: Account<'info, Casino>,
     
+    /// Round the bet joins, bets close once it is resolved
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Bet::LEN,
+        seeds = [b"bet", casino.key().as_ref(), player.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerLimits::LEN,
+        seeds = [b"player_limits", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_limits: Account<'info, PlayerLimits>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player_profile", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    
+    /// Required while the casino is gated
+    #[account(
+        seeds = [b"attestation", casino.key().as_ref(), player.key().as_ref()],
+        bump = player_attestation.bump
+    )]
+    pub player_attestation: Option<Account<'info, PlayerAttestation>>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IssueFreeBet<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FreeBet::LEN,
+        seeds = [b"free_bet", casino.key().as_ref(), &casino.free_bet_count.to_le_bytes()],
+        bump
+    )]
+    pub free_bet: Account<'info, FreeBet>,
+    
+    /// CHECK: Only recorded as the voucher's eligible player
+    pub player: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceFreeBet<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"free_bet", casino.key().as_ref(), &free_bet.id.to_le_bytes()],
+        bump = free_bet.bump
+    )]
+    pub free_bet: Account<'info, FreeBet>,
+    
+    /// Round the bet joins, bets close once it is resolved
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Bet::LEN,
+        seeds = [b"bet", casino.key().as_ref(), player.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerLimits::LEN,
+        seeds = [b"player_limits", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_limits: Account<'info, PlayerLimits>,
+    
+    /// Required while the casino is gated
+    #[account(
+        seeds = [b"attestation", casino.key().as_ref(), player.key().as_ref()],
+        bump = player_attestation.bump
+    )]
+    pub player_attestation: Option<Account<'info, PlayerAttestation>>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleJackpot<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        seeds = [b"game", casino.key().as_ref(), &bet.game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        mut,
+        seeds = [b"bet", casino.key().as_ref(), bet.player.as_ref(), &bet.game_id.to_le_bytes()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == bet.player @ CasinoError::Unauthorized
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RequestCrashVrf<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    /// Round being replaced, required once the first round has started
+    #[account(
+        seeds = [b"game", casino.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        bump
+    )]
+    pub current_game: Option<Account<'info, Game>>,
+    
+    /// Game for the round being started, created when randomness is requested
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Game::LEN,
+        seeds = [b"game", casino.key().as_ref(), &(casino.current_game_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    // Switchboard VRF accounts
+    pub vrf_account: AccountInfo<'info>,
+    pub oracle_queue: AccountInfo<'info>,
+    pub program_state: AccountInfo<'info>,
+    pub escrow: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveCrash<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VoidRound<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &game.game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+}
+
+#[derive(Accounts)]
+pub struct Cashout<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &bet.game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, has_one = player)]
+    pub bet: Account<'info, Bet>,
+    
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBet<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &bet.game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        mut,
+        seeds = [b"bet", casino.key().as_ref(), bet.player.as_ref(), &bet.game_id.to_le_bytes()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player_profile", casino.key().as_ref(), bet.player.as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    
+    /// Required when the player has a referrer
+    #[account(
+        mut,
+        seeds = [b"referrer", casino.key().as_ref(), player_profile.referrer.as_ref()],
+        bump = referrer_account.bump
+    )]
+    pub referrer_account: Option<Account<'info, Referrer>>,
+    
+    /// Credits the payout against today's loss limit, every bettor has one from placing the bet
+    #[account(
+        mut,
+        seeds = [b"player_limits", casino.key().as_ref(), bet.player.as_ref()],
+        bump = player_limits.bump
+    )]
+    pub player_limits: Account<'info, PlayerLimits>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == bet.player @ CasinoError::Unauthorized
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(referrer: Pubkey)]
+pub struct RegisterReferrer<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player_profile", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + Referrer::LEN,
+        seeds = [b"referrer", casino.key().as_ref(), referrer.as_ref()],
+        bump
+    )]
+    pub referrer_account: Account<'info, Referrer>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"referrer", casino.key().as_ref(), referrer.key().as_ref()],
+        bump = referrer_account.bump
+    )]
+    pub referrer_account: Account<'info, Referrer>,
+    
+    pub referrer: Signer<'info>,
+    
+    #[account(mut)]
+    pub referrer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRakeback<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_profile", casino.key().as_ref(), player.key().as_ref()],
+        bump = player_profile.bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferrerTier<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"referrer", casino.key().as_ref(), referrer_account.referrer.as_ref()],
+        bump = referrer_account.bump
+    )]
+    pub referrer_account: Account<'info, Referrer>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPlayerLimits<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerLimits::LEN,
+        seeds = [b"player_limits", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_limits: Account<'info, PlayerLimits>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperatorLimits<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PlayerLimits::LEN,
+        seeds = [b"player_limits", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_limits: Account<'info, PlayerLimits>,
+    
+    /// CHECK: Only used as the limits PDA seed
+    pub player: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitAttestation<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerAttestation::LEN,
+        seeds = [b"attestation", casino.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, PlayerAttestation>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    /// CHECK: Address constrained to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeLp<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = 8 + Stake::LEN,
+        seeds = [b"stake", casino.key().as_ref(), staker.key().as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    
+    #[account(mut)]
+    pub staker_lp_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"staking_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub staking_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundBet<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"bet", casino.key().as_ref(), bet.player.as_ref(), &bet.game_id.to_le_bytes()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    /// Round of the bet, its stake and jackpot share are taken back out of it
+    #[account(
+        mut,
+        seeds = [b"game", casino.key().as_ref(), &bet.game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    /// Takes the refunded wager back out of the player's stats when provided
+    #[account(
+        mut,
+        seeds = [b"player_profile", casino.key().as_ref(), bet.player.as_ref()],
+        bump = player_profile.bump
+    )]
+    pub player_profile: Option<Account<'info, PlayerProfile>>,
+    
+    /// Takes the refunded stake back out of today's limits, every bettor has one from placing the bet
+    #[account(
+        mut,
+        seeds = [b"player_limits", casino.key().as_ref(), bet.player.as_ref()],
+        bump = player_limits.bump
+    )]
+    pub player_limits: Account<'info, PlayerLimits>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == bet.player @ CasinoError::Unauthorized
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeLp<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        constraint = stake.is_owner(&staker.key(), &position_token_account) @ CasinoError::Unauthorized
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    
+    /// Required once the stake is tokenized, proves the signer holds the position
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub staker_lp_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"staking_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub staking_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    /// Required when the staking vault holds bankroll shares
+    #[account(
+        seeds = [b"lp_mint", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Option<Account<'info, Mint>>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> UnstakeLp<'info> {
+    /// Return staked LP tokens, bounded by the vault's free balance when they back the bankroll
+    fn release_stake(&mut self, amount: u64, vault_bump: u8, remaining_accounts: &[AccountInfo]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(self.stake.amount >= amount, CasinoError::InsufficientStake);
+        // Locks no longer hold once the casino is winding down, their boost vests at once
+        require!(
+            now >= self.stake.lock_end || self.casino.is_shutdown,
+            CasinoError::StakeLocked
+        );
+        self.stake.lock_end = self.stake.lock_end.min(now);
+
+        // Staked bankroll shares can only leave against uncommitted vault balance
+        // Casinos that never issued shares stake external LP tokens and need no LP mint
+        let casino_key = self.casino.key();
+        let (lp_mint_key, _) = Pubkey::find_program_address(
+            &[b"lp_mint".as_ref(), casino_key.as_ref()],
+            &crate::ID,
+        );
+        if self.staking_vault.mint == lp_mint_key {
+            let lp_mint = self.lp_mint.as_ref().ok_or(CasinoError::LpMintRequired)?;
+            let bankroll = self.casino.bankroll_balance(self.vault.amount);
+            let value = calculate_withdraw_amount(
+                amount,
+                bankroll,
+                lp_mint.supply,
+            )?;
+            let free_balance = calculate_free_balance(
+                bankroll,
+                self.casino.round_exposure,
+            );
+            require!(value <= free_balance, CasinoError::InsufficientFreeBalance);
+        }
+
+        self.transfer_lp_tokens(amount, vault_bump)?;
+
+        let streams = load_reward_streams(&self.casino, remaining_accounts, now)?;
+        self.stake.accrue_rewards(self.casino.acc_reward_per_weight, &streams, now)?;
+        self.stake.amount = self.stake.amount
+            .checked_sub(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+        self.stake.update_weight(&mut self.casino, &streams, now)?;
+
+        // Never leave a pending request larger than what remains staked
+        self.stake.unstake_amount = self.stake.unstake_amount.min(self.stake.amount);
+
+        self.casino.total_staked = self.casino.total_staked
+            .checked_sub(amount)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        emit!(LpUnstaked {
+            staker: self.stake.staker,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Transfer LP tokens from the staking vault back to the staker, signed with the vault's canonical bump
+    fn transfer_lp_tokens(&self, amount: u64, vault_bump: u8) -> Result<()> {
+        let casino_key = self.casino.key();
+        let seeds = &[
+            b"staking_vault".as_ref(),
+            casino_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: self.staking_vault.to_account_info(),
+            to: self.staker_lp_account.to_account_info(),
+            authority: self.staking_vault.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        constraint = stake.is_owner(&staker.key(), &position_token_account) @ CasinoError::Unauthorized
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    pub staker: Signer<'info>,
+    
+    /// Required once the stake is tokenized, proves the signer holds the position
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct CompoundRewards<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", casino.key().as_ref(), stake.staker.as_ref()],
+        bump = stake.bump
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    pub caller: Signer<'info>,
+    
+    /// Lets the holder of a tokenized stake compound without auto-compound enabled
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Receives the keeper reward when a keeper compounds for the staker
+    #[account(mut, token::mint = fee_vault.mint)]
+    pub keeper_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"staking_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub staking_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_mint", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    
+    /// Holds the shares locked when compounding makes the first deposit
+    #[account(
+        mut,
+        seeds = [b"lp_escrow", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_escrow: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    #[account(
+        mut,
+        constraint = stake.is_owner(&staker.key(), &position_token_account) @ CasinoError::Unauthorized
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    pub staker: Signer<'info>,
+    
+    /// Required once the stake is tokenized, proves the signer holds the position
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLock<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        constraint = stake.is_owner(&staker.key(), &position_token_account) @ CasinoError::Unauthorized
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    pub staker: Signer<'info>,
+    
+    /// Required once the stake is tokenized, proves the signer holds the position
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct TokenizeStake<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(mut, has_one = staker)]
+    pub stake: Account<'info, Stake>,
+    
+    #[account(
+        init,
+        payer = staker,
+        seeds = [b"position_mint", stake.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = casino
+    )]
+    pub position_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = staker,
+        associated_token::mint = position_mint,
+        associated_token::authority = staker
+    )]
+    pub staker_position_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshStake<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", casino.key().as_ref(), stake.staker.as_ref()],
+        bump = stake.bump
+    )]
+    pub stake: Account<'info, Stake>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        constraint = stake.is_owner(&staker.key(), &position_token_account) @ CasinoError::Unauthorized
+    )]
+    pub stake: Account<'info, Stake>,
+    
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    
+    /// Required once the stake is tokenized, proves the signer holds the position
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub staker_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquidity<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    
+    #[account(mut)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub provider_lp_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_mint", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    
+    /// Holds the shares locked by the first deposit
+    #[account(
+        mut,
+        seeds = [b"lp_escrow", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_escrow: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLiquidity<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    
+    #[account(mut)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub provider_lp_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_mint", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = provider,
+        space = 8 + WithdrawRequest::LEN,
+        seeds = [b"withdraw_request", casino.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+    
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    
+    #[account(mut)]
+    pub provider_lp_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_escrow", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_escrow: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessWithdrawal<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"withdraw_request", casino.key().as_ref(), owner.key().as_ref()],
+        bump = withdraw_request.bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+    
+    /// Round the request waits on, omitted for requests made before the first round
+    #[account(
+        seeds = [b"game", casino.key().as_ref(), &withdraw_request.game_id.to_le_bytes()],
+        bump
+    )]
+    pub game: Option<Account<'info, Game>>,
+    
+    /// CHECK: Rent receiver, bound to the request by its PDA seeds
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_escrow", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_escrow: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_mint", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        close = provider,
+        seeds = [b"withdraw_request", casino.key().as_ref(), provider.key().as_ref()],
+        bump = withdraw_request.bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+    
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    
+    #[account(mut)]
+    pub provider_lp_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"lp_escrow", casino.key().as_ref()],
+        bump
+    )]
+    pub lp_escrow: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLpMint<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_mint", casino.key().as_ref()],
+        bump,
+        mint::decimals = bet_mint.decimals,
+        mint::authority = casino
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    
+    #[account(
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_escrow", casino.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = casino
+    )]
+    pub lp_escrow: Account<'info, TokenAccount>,
+    
+    #[account(address = vault.mint)]
+    pub bet_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
     #[account(
         init,
-        payer = player,
-        space = 8 + Bet::LEN,
-        seeds = [b"bet", casino.key().as_ref(), player.key().as_ref(), &casino.current_game_id.to_le_bytes()],
+        payer = treasurer,
+        space = 8 + TreasuryWithdrawal::LEN,
+        seeds = [b"treasury_withdrawal", casino.key().as_ref(), &casino.treasury_withdrawal_count.to_le_bytes()],
         bump
     )]
-    pub bet: Account<'info, Bet>,
+    pub withdrawal: Account<'info, TreasuryWithdrawal>,
     
     #[account(mut)]
-    pub player: Signer<'info>,
+    pub treasurer: Signer<'info>,
     
     #[account(mut)]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub destination: Account<'info, TokenAccount>,
     
     #[account(
         mut,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterRewardStream<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RewardStream::LEN,
+        seeds = [b"reward_stream", casino.key().as_ref(), &[casino.reward_stream_count]],
+        bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reward_stream_vault", reward_stream.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = casino
+    )]
+    pub stream_vault: Account<'info, TokenAccount>,
+    
+    pub reward_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeVault<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"fee_vault", casino.key().as_ref()],
+        bump,
+        token::mint = bet_mint,
+        token::authority = casino
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vault", casino.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(address = vault.mint)]
+    pub bet_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
         seeds = [b"vault", casino.key().as_ref()],
-        bump = casino.bump
+        bump,
+        token::mint = bet_mint,
+        token::authority = vault
     )]
     pub vault: Account<'info, TokenAccount>,
     
+    pub bet_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStakingVault<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"staking_vault", casino.key().as_ref()],
+        bump,
+        token::mint = staking_mint,
+        token::authority = staking_vault
+    )]
+    pub staking_vault: Account<'info, TokenAccount>,
+    
+    pub staking_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingConfigChange::LEN,
+        seeds = [b"config_change", casino.key().as_ref()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeConfigChange<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"config_change", casino.key().as_ref()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateCasino<'info> {
+    /// CHECK: May still be in an older layout, checked and deserialized after realloc
+    #[account(
+        mut,
+        seeds = [b"casino"],
+        bump
+    )]
+    pub casino: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    pub casino: Account<'info, Casino>,
+    
+    /// CHECK: Program ownership and discriminator are checked before upgrading
+    #[account(mut)]
+    pub target: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut)]
+    pub casino: Account<'info, Casino>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Casino {
+    pub authority: Pubkey,
+    pub house_edge_bps: u16, // Basis points (e.g., 200 = 2%)
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub pause_flags: u8, // PAUSE_* bits for independently paused operations
+    pub bump: u8,
+    pub current_game_id: u64,
+    pub total_volume: u64,
+    pub total_fees: u64,
+    pub total_staked: u64,
+    pub round_exposure: u64, // Max payout of all pending bets, across rounds
+    pub unstake_cooldown: i64, // Seconds between request_unstake and complete_unstake
+    pub total_weight: u64, // Sum of boosted stake weights
+    pub acc_reward_per_weight: u128, // Fees per unit of weight, scaled by REWARD_PRECISION
+    pub fee_split: FeeSplit,
+    // Fee buckets held in the fee vault
+    pub staker_pool: u64,
+    pub treasury_pool: u64,
+    pub referral_pool: u64,
+    pub jackpot_pool: u64,
+    pub treasurer: Pubkey,
+    pub treasury_epoch_limit: u64, // Max treasury withdrawal per epoch
+    pub treasury_epoch: u64, // Epoch of the last treasury withdrawal
+    pub treasury_withdrawn_in_epoch: u64,
+    pub treasury_withdrawal_count: u64,
+    pub reward_stream_count: u8,
+    pub pending_authority: Option<Pubkey>, // Proposed admin awaiting accept_authority
+    pub operator: Pubkey, // Starts rounds and resolves them
+    pub guardian: Pubkey, // May pause, only admin unpauses
+    pub risk_manager: Pubkey,
+    pub curve_scale: u32, // Crash curve scale factor in basis points
+    pub max_payout: u64, // Gross payout cap per bet
+    pub max_exposure_bps: u16, // Round exposure cap as a share of the vault, 0 disables
+    pub betting_window: i64, // Seconds after round start that bets are accepted, 0 disables
+    pub round_started_at: i64,
+    pub keeper_reward_bps: u16, // Keeper's cut of rewards it compounds for others
+    pub version: u8,
+    pub is_shutdown: bool, // Irreversible wind-down, only refunds and exits remain
+    pub shutdown_game_id: u64, // Live round when the casino was shut down
+    pub round_wagered: u64, // Stakes of all pending bets not yet owed back as refunds
+    pub refund_liability: u64, // Stakes owed back to bettors of a cancelled round
+    pub vrf_timeout_slots: u64, // Slots after a randomness request before the round can be voided
+    pub current_round_voided: bool,
+    pub reserved_v1: [u8; ACCOUNT_RESERVED - 34], // Unused tail of the v1 reserved space
+    pub breaker_threshold_bps: u16, // Window losses that pause bets as a share of the bankroll, 0 disables
+    pub breaker_window: i64, // Seconds per circuit breaker window
+    pub breaker_window_start: i64,
+    pub breaker_window_bankroll: u64, // Bankroll when the window started
+    pub breaker_net_flow: i64, // Wagers in less payouts out during the window
+    pub compliance_signer: Pubkey, // Signs player attestations for gated mode
+    pub gated: bool, // Bets require a live attestation from the compliance signer
+    pub referral_rates_bps: [u16; REFERRAL_TIER_COUNT], // Referrer share of house fees per tier
+    pub reserved_v2: [u8; ACCOUNT_RESERVED - 33 - 2 * REFERRAL_TIER_COUNT], // Unused tail of the v2 reserved space
+    pub vip_tiers: [VipTier; VIP_TIER_COUNT], // Ascending wagered-volume tiers with rakeback and bet limit perks
+    pub rakeback_pool: u64, // Accrued player rakeback held in the fee vault
+    pub promo: Pubkey, // Issues free bet vouchers
+    pub free_bet_count: u64,
+    pub jackpot_trigger_multiplier: u64, // Crash point at or above which the jackpot is won, 0 disables
+    pub reserved: [u8; ACCOUNT_RESERVED - 48],
+}
+
+impl Casino {
+    pub const LEN: usize = 32 + 2 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 16
+        + FeeSplit::LEN + 8 + 8 + 8 + 8
+        + 32 + 8 + 8 + 8 + 8
+        + 1
+        + 33 + 32 + 32 + 32
+        + 4 + 8 + 2 + 8 + 8 + 2
+        + 1 + ACCOUNT_RESERVED
+        + 2 + 8 + 8 + 8 + 8 + ACCOUNT_RESERVED
+        + VipTier::LEN * VIP_TIER_COUNT + 8 + ACCOUNT_RESERVED;
+
+    /// Current value of every configurable parameter
+    pub fn config(&self) -> ConfigParams {
+        ConfigParams {
+            house_edge_bps: Some(self.house_edge_bps),
+            min_bet: Some(self.min_bet),
+            max_bet: Some(self.max_bet),
+            fee_split: Some(self.fee_split),
+            curve_scale: Some(self.curve_scale),
+            max_payout: Some(self.max_payout),
+            max_exposure_bps: Some(self.max_exposure_bps),
+            betting_window: Some(self.betting_window),
+            keeper_reward_bps: Some(self.keeper_reward_bps),
+        }
+    }
+
+    /// Validate and apply the set fields of a config change
+    /// Returns the previous values of the changed fields
+    pub fn apply_config(&mut self, params: &ConfigParams) -> Result<ConfigParams> {
+        let current = self.config();
+        params.validate(&current)?;
+
+        // A lower payout cap must still cover the raised bet limits of the VIP tiers
+        if let Some(max_payout) = params.max_payout {
+            validate_vip_tiers(&self.vip_tiers, max_payout)?;
+        }
+
+        if let Some(house_edge_bps) = params.house_edge_bps {
+            self.house_edge_bps = house_edge_bps;
+        }
+        if let Some(min_bet) = params.min_bet {
+            self.min_bet = min_bet;
+        }
+        if let Some(max_bet) = params.max_bet {
+            self.max_bet = max_bet;
+        }
+        if let Some(fee_split) = params.fee_split {
+            self.fee_split = fee_split;
+        }
+        if let Some(curve_scale) = params.curve_scale {
+            self.curve_scale = curve_scale;
+        }
+        if let Some(max_payout) = params.max_payout {
+            self.max_payout = max_payout;
+        }
+        if let Some(max_exposure_bps) = params.max_exposure_bps {
+            self.max_exposure_bps = max_exposure_bps;
+        }
+        if let Some(betting_window) = params.betting_window {
+            self.betting_window = betting_window;
+        }
+        if let Some(keeper_reward_bps) = params.keeper_reward_bps {
+            self.keeper_reward_bps = keeper_reward_bps;
+        }
+
+        Ok(current.masked(params))
+    }
+
+    /// Whether a key holds a role, the admin role belongs to `authority`
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        let holder = match role {
+            Role::Admin => self.authority,
+            Role::Operator => self.operator,
+            Role::Guardian => self.guardian,
+            Role::Treasurer => self.treasurer,
+            Role::RiskManager => self.risk_manager,
+            Role::Promo => self.promo,
+        };
+        holder == *key
+    }
+
+    /// Vault balance owned by LPs, excluding stakes owed back to refunded bettors
+    pub fn bankroll_balance(&self, vault_balance: u64) -> u64 {
+        vault_balance.saturating_sub(self.refund_liability)
+    }
+
+    /// Price a bankroll deposit against the bankroll before it lands, as withdrawals do
+    /// Returns the depositor's shares and the minimum shares the first deposit locks for good
+    pub fn price_lp_deposit(&self, amount: u64, vault_balance: u64, lp_supply: u64) -> Result<(u64, u64)> {
+        let bankroll = self.bankroll_balance(vault_balance);
+        let lp_amount = calculate_lp_to_mint(amount, bankroll, lp_supply)?;
+        require!(lp_amount > 0, CasinoError::InvalidLiquidityAmount);
+
+        let locked_lp = if lp_supply == 0 { MINIMUM_LIQUIDITY } else { 0 };
+        let depositor_lp = lp_amount
+            .checked_sub(locked_lp)
+            .ok_or(CasinoError::MathOverflow)?;
+        Ok((depositor_lp, locked_lp))
+    }
+
+    /// Record house P&L for the circuit breaker, rolling to a new window from the current bankroll
+    /// Pauses new bets and returns true when window losses cross the threshold, never fails
+    pub fn record_house_flow(&mut self, flow: i64, vault_balance: u64, now: i64) -> bool {
+        if now >= self.breaker_window_start.saturating_add(self.breaker_window) {
+            self.breaker_window_start = now;
+            self.breaker_window_bankroll = self.bankroll_balance(vault_balance);
+            self.breaker_net_flow = 0;
+        }
+        self.breaker_net_flow = self.breaker_net_flow.saturating_add(flow);
+
+        let tripped = self.pause_flags & PAUSE_BETS == 0
+            && is_drawdown_exceeded(
+                self.breaker_net_flow,
+                self.breaker_window_bankroll,
+                self.breaker_threshold_bps,
+            );
+        if tripped {
+            self.pause_flags |= PAUSE_BETS;
+        }
+        tripped
+    }
+
+    /// Check the signer holds a role
+    pub fn require_role(&self, role: Role, signer: &Pubkey) -> Result<()> {
+        require!(self.has_role(role, signer), CasinoError::Unauthorized);
+        Ok(())
+    }
+
+    /// Fail if any of the given PAUSE_* operations are paused
+    pub fn require_not_paused(&self, flags: u8) -> Result<()> {
+        require!(self.pause_flags & flags == 0, CasinoError::GamePaused);
+        Ok(())
+    }
+
+    /// Check the live round is taking bets
+    pub fn require_betting_open(&self, now: i64) -> Result<()> {
+        self.require_not_paused(PAUSE_BETS)?;
+        require!(!self.current_round_voided, CasinoError::RoundVoided);
+        if self.betting_window > 0 {
+            require!(
+                now <= self.round_started_at.saturating_add(self.betting_window),
+                CasinoError::BettingClosed
+            );
+        }
+        Ok(())
+    }
+
+    /// Gated casinos only take bets from players with a live compliance attestation
+    pub fn require_attestation(&self, attestation: Option<&PlayerAttestation>, now: i64) -> Result<()> {
+        if self.gated {
+            let attestation = attestation.ok_or(CasinoError::AttestationRequired)?;
+            require!(
+                attestation.signer == self.compliance_signer && now < attestation.expiry,
+                CasinoError::AttestationExpired
+            );
+        }
+        Ok(())
+    }
+
+    /// Split a house fee across the fee buckets
+    /// Returns the amount to move from the bankroll vault into the fee vault,
+    /// the staker share stays in the bankroll while nothing is staked
+    /// Only `referral_credit`, what the player's referrer earned, is kept for referrals,
+    /// the rest of that bucket goes to stakers, or the treasury while nothing is staked
+    pub fn route_fee(&mut self, fee: u64, referral_credit: u64) -> Result<u64> {
+        let allocation = self.fee_split.split(fee)?;
+        let unearned_referral = allocation.referral
+            .checked_sub(referral_credit)
+            .ok_or(CasinoError::MathOverflow)?;
+        let mut routed = fee;
+        let mut treasury_fee = allocation.treasury;
+
+        if self.total_weight == 0 {
+            routed = routed
+                .checked_sub(allocation.staker)
+                .ok_or(CasinoError::MathOverflow)?;
+            treasury_fee = treasury_fee
+                .checked_add(unearned_referral)
+                .ok_or(CasinoError::MathOverflow)?;
+        } else {
+            let staker_fee = allocation.staker
+                .checked_add(unearned_referral)
+                .ok_or(CasinoError::MathOverflow)?;
+            self.distribute_staker_fees(staker_fee)?;
+            self.staker_pool = self.staker_pool
+                .checked_add(staker_fee)
+                .ok_or(CasinoError::MathOverflow)?;
+        }
+
+        self.treasury_pool = self.treasury_pool
+            .checked_add(treasury_fee)
+            .ok_or(CasinoError::MathOverflow)?;
+        self.referral_pool = self.referral_pool
+            .checked_add(referral_credit)
+            .ok_or(CasinoError::MathOverflow)?;
+        self.jackpot_pool = self.jackpot_pool
+            .checked_add(allocation.jackpot)
+            .ok_or(CasinoError::MathOverflow)?;
+
+        Ok(routed)
+    }
+
+    /// Credit fees to stakers pro-rata by weight
+    /// With nothing staked the fees stay in the vault with the bankroll
+    pub fn distribute_staker_fees(&mut self, fees: u64) -> Result<()> {
+        self.acc_reward_per_weight = calculate_reward_per_weight(
+            self.acc_reward_per_weight,
+            fees,
+            self.total_weight,
+        )?;
+        Ok(())
+    }
+}
+
+impl Versioned for Casino {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        if self.version < 1 {
+            // Fields added before versioning read as zero on legacy casinos
+            if self.pause_flags != 0 {
+                // Legacy `is_paused` stopped everything
+                self.pause_flags = PAUSE_ALL;
+            }
+            if self.fee_split == FeeSplit::default() {
+                self.fee_split = FeeSplit::STAKERS_ONLY;
+            }
+            if self.treasurer == Pubkey::default() {
+                self.treasurer = self.authority;
+            }
+            if self.operator == Pubkey::default() {
+                self.operator = self.authority;
+            }
+            if self.guardian == Pubkey::default() {
+                self.guardian = self.authority;
+            }
+            if self.risk_manager == Pubkey::default() {
+                self.risk_manager = self.authority;
+            }
+            if self.curve_scale == 0 {
+                self.curve_scale = DEFAULT_CURVE_SCALE;
+            }
+            if self.max_payout == 0 {
+                self.max_payout = u64::MAX;
+            }
+        }
+        if self.version < 2 {
+            if self.vrf_timeout_slots == 0 {
+                self.vrf_timeout_slots = DEFAULT_VRF_TIMEOUT_SLOTS;
+            }
+            self.breaker_threshold_bps = DEFAULT_BREAKER_THRESHOLD_BPS;
+            self.breaker_window = DEFAULT_BREAKER_WINDOW;
+        }
+        if self.version < 3 {
+            self.promo = self.authority;
+        }
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+#[account]
+pub struct Bet {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub auto_cashout_multiplier: Option<u64>, // Basis points
+    pub status: BetStatus,
+    pub game_id: u64,
+    pub cashout_multiplier: Option<u64>, // Locked while the round is in flight, paid on settlement
+    pub bump: u8,
+    pub max_payout: u64, // Casino payout cap when the bet was placed
+    pub version: u8,
+    pub is_free_bet: bool, // Placed with a voucher, pays profit only
+    pub min_cashout_multiplier: u64, // Voucher's lowest cashout multiplier, 0 for paid bets
+    pub jackpot_settled: bool,
+    pub reserved: [u8; ACCOUNT_RESERVED - 10],
 }
 
-#[derive(Accounts)]
-pub struct RequestCrashVrf<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    pub authority: Signer<'info>,
-    
-    // Switchboard VRF accounts
-    pub vrf_account: AccountInfo<'info>,
-    pub oracle_queue: AccountInfo<'info>,
-    pub program_state: AccountInfo<'info>,
-    pub escrow: AccountInfo<'info>,
+impl Bet {
+    pub const LEN: usize = 32 + 8 + 9 + 1 + 8 + 9 + 1 + 8 + 1 + ACCOUNT_RESERVED;
+
+    /// Net payout and house fee for cashing out at `multiplier`
+    pub fn payout_at(&self, multiplier: u64, house_edge_bps: u16) -> Result<(u64, u64)> {
+        if self.is_free_bet {
+            calculate_free_bet_payout(self.amount, multiplier, self.max_payout, house_edge_bps)
+        } else {
+            calculate_payout(self.amount, multiplier, self.max_payout, house_edge_bps)
+        }
+    }
+
+    /// Multiplier the bet leaves its round at, the lower of a locked cashout and its auto-cashout
+    pub fn exit_multiplier(&self) -> Option<u64> {
+        match (self.cashout_multiplier, self.auto_cashout_multiplier) {
+            (Some(cashout), Some(auto_cashout)) => Some(cashout.min(auto_cashout)),
+            (cashout, auto_cashout) => cashout.or(auto_cashout),
+        }
+    }
+
+    /// Worst-case payout the bet holds against the bankroll while pending
+    pub fn exposure(&self) -> Result<u64> {
+        Ok(calculate_bet_exposure(self.amount, self.exit_multiplier())?.min(self.max_payout))
+    }
+
+    /// Tokens the player put into the vault, nothing for free bets
+    pub fn stake(&self) -> u64 {
+        if self.is_free_bet {
+            0
+        } else {
+            self.amount
+        }
+    }
 }
 
-#[derive(Accounts)]
-pub struct ResolveCrash<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Game::LEN,
-        seeds = [b"game", casino.key().as_ref(), &casino.current_game_id.to_le_bytes()],
-        bump
-    )]
-    pub game: Account<'info, Game>,
-    
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+impl Versioned for Bet {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        if self.version < 1 && self.max_payout == 0 {
+            // Legacy bets were placed without a payout cap
+            self.max_payout = u64::MAX;
+        }
+        self.version = ACCOUNT_VERSION;
+    }
 }
 
-#[derive(Accounts)]
-pub struct Cashout<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    #[account(mut)]
-    pub game: Account<'info, Game>,
-    
-    #[account(mut, has_one = player)]
-    pub bet: Account<'info, Bet>,
-    
-    pub player: Signer<'info>,
-    
-    #[account(mut)]
-    pub player_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        seeds = [b"vault", casino.key().as_ref()],
-        bump = casino.bump
-    )]
-    pub vault: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
+#[account]
+pub struct Game {
+    pub game_id: u64,
+    pub crash_multiplier: u64, // Basis points (e.g., 15000 = 1.5x)
+    pub is_resolved: bool,
+    pub version: u8,
+    pub status: GameStatus,
+    pub request_slot: u64, // Slot randomness was requested in
+    pub total_wagered: u64, // Paid stakes still in the round, owed back if it is voided, refunds take theirs out
+    pub jackpot_amount: u64, // Jackpot awarded to the round, 0 when none
+    pub exposure: u64, // Max payout of the round's pending bets
+    pub launched_at: i64, // When bets closed and the live multiplier started climbing
+    pub reserved: [u8; ACCOUNT_RESERVED - 41],
 }
 
-#[derive(Accounts)]
-pub struct StakeLp<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    #[account(
-        init_if_needed,
-        payer = staker,
-        space = 8 + Stake::LEN,
-        seeds = [b"stake", casino.key().as_ref(), staker.key().as_ref()],
-        bump
-    )]
-    pub stake: Account<'info, Stake>,
-    
-    #[account(mut)]
-    pub staker: Signer<'info>,
-    
-    #[account(mut)]
-    pub staker_lp_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        seeds = [b"staking_vault", casino.key().as_ref()],
-        bump = casino.bump
-    )]
-    pub staking_vault: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+impl Game {
+    pub const LEN: usize = 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
 }
 
-#[derive(Accounts)]
-pub struct UnstakeLp<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    #[account(mut, has_one = staker)]
-    pub stake: Account<'info, Stake>,
-    
-    #[account(mut)]
-    pub staker: Signer<'info>,
-    
-    #[account(mut)]
-    pub staker_lp_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        seeds = [b"staking_vault", casino.key().as_ref()],
-        bump = casino.bump
-    )]
-    pub staking_vault: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
+impl Versioned for Game {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        if self.version < 1 && self.is_resolved {
+            self.status = GameStatus::Resolved;
+        }
+        self.version = ACCOUNT_VERSION;
+    }
 }
 
-#[derive(Accounts)]
-pub struct ClaimRewards<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    #[account(mut, has_one = staker)]
-    pub stake: Account<'info, Stake>,
-    
-    #[account(mut)]
-    pub staker: Signer<'info>,
-    
-    #[account(mut)]
-    pub staker_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        seeds = [b"vault", casino.key().as_ref()],
-        bump = casino.bump
-    )]
-    pub vault: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
+#[account]
+pub struct Stake {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub claimed_rewards: u64,
+    pub bump: u8,
+    pub unstake_amount: u64, // Pending amount in cooldown
+    pub unstake_available_at: i64,
+    pub lock_end: i64, // Boost decays to 1x at this time
+    pub weight: u64, // Boosted reward weight, refreshed on every interaction
+    pub reward_debt: u128,
+    pub pending_rewards: u64,
+    pub auto_compound: bool, // Keepers may compound on the staker's behalf
+    pub stream_rewards: [StreamCheckpoint; MAX_REWARD_STREAMS], // Indexed by RewardStream::index
+    pub position_mint: Option<Pubkey>, // Set once the stake is tokenized
+    pub version: u8,
+    pub locked_rewards: u64, // Fee rewards earned by the lock boost, claimable once the lock ends
+    pub locked_stream_rewards: [u64; MAX_REWARD_STREAMS], // Stream rewards earned by the lock boost
+    pub reserved: [u8; ACCOUNT_RESERVED - 8 - 8 * MAX_REWARD_STREAMS],
+}
+
+impl Stake {
+    pub const LEN: usize = 32 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 16 + 8 + 1
+        + StreamCheckpoint::LEN * MAX_REWARD_STREAMS
+        + 33 + 1 + ACCOUNT_RESERVED;
+
+    /// Whether `owner` controls this stake: the staker, or the position token holder once tokenized
+    pub fn is_owner(&self, owner: &Pubkey, position_token_account: &Option<Account<TokenAccount>>) -> bool {
+        match self.position_mint {
+            None => *owner == self.staker,
+            Some(position_mint) => position_token_account.as_ref().is_some_and(|account| {
+                account.mint == position_mint && account.owner == *owner && account.amount == 1
+            }),
+        }
+    }
+
+    /// Move fees and stream rewards earned at the current weight into pending rewards
+    /// The lock boost's share is held until the lock ends, so breaking a lock forfeits it
+    /// Call before changing the stake's amount or lock
+    pub fn accrue_rewards(&mut self, acc_reward_per_weight: u128, streams: &[RewardStream], now: i64) -> Result<()> {
+        let unlocked = now >= self.lock_end;
+
+        let accrued = calculate_accrued_rewards(self.weight, acc_reward_per_weight, self.reward_debt)?;
+        let (base, boost) = split_boost_rewards(accrued, self.amount, self.weight)?;
+        self.locked_rewards = self.locked_rewards
+            .checked_add(boost)
+            .ok_or(CasinoError::MathOverflow)?;
+        self.pending_rewards = self.pending_rewards
+            .checked_add(base)
+            .ok_or(CasinoError::MathOverflow)?;
+        if unlocked {
+            self.pending_rewards = self.pending_rewards
+                .checked_add(self.locked_rewards)
+                .ok_or(CasinoError::MathOverflow)?;
+            self.locked_rewards = 0;
+        }
+        self.reward_debt = calculate_reward_debt(self.weight, acc_reward_per_weight)?;
+
+        for stream in streams {
+            let index = stream.index as usize;
+            let checkpoint = &mut self.stream_rewards[index];
+            let accrued = calculate_accrued_rewards(
+                self.weight,
+                stream.acc_reward_per_weight,
+                checkpoint.reward_debt,
+            )?;
+            let (base, boost) = split_boost_rewards(accrued, self.amount, self.weight)?;
+            let locked = &mut self.locked_stream_rewards[index];
+            *locked = locked.checked_add(boost).ok_or(CasinoError::MathOverflow)?;
+            checkpoint.pending_rewards = checkpoint.pending_rewards
+                .checked_add(base)
+                .ok_or(CasinoError::MathOverflow)?;
+            if unlocked {
+                checkpoint.pending_rewards = checkpoint.pending_rewards
+                    .checked_add(*locked)
+                    .ok_or(CasinoError::MathOverflow)?;
+                *locked = 0;
+            }
+            checkpoint.reward_debt = calculate_reward_debt(self.weight, stream.acc_reward_per_weight)?;
+        }
+        Ok(())
+    }
+
+    /// Recompute the boosted weight and keep the casino total in sync
+    /// Call after accrue_rewards and any change to amount or lock
+    pub fn update_weight(&mut self, casino: &mut Casino, streams: &[RewardStream], now: i64) -> Result<()> {
+        let weight = calculate_stake_weight(self.amount, self.lock_end, now)?;
+        casino.total_weight = casino.total_weight
+            .checked_sub(self.weight)
+            .and_then(|x| x.checked_add(weight))
+            .ok_or(CasinoError::MathOverflow)?;
+        self.weight = weight;
+        self.reward_debt = calculate_reward_debt(weight, casino.acc_reward_per_weight)?;
+
+        for stream in streams {
+            self.stream_rewards[stream.index as usize].reward_debt =
+                calculate_reward_debt(weight, stream.acc_reward_per_weight)?;
+        }
+        Ok(())
+    }
+}
+
+impl Versioned for Stake {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+#[account]
+pub struct WithdrawRequest {
+    pub owner: Pubkey,
+    pub lp_amount: u64,
+    pub game_id: u64, // Round that must settle before payout
+    pub requested_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
+}
+
+impl WithdrawRequest {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+}
+
+impl Versioned for WithdrawRequest {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+#[account]
+pub struct PlayerLimits {
+    pub player: Pubkey,
+    pub limits: LimitSettings, // Set by the player
+    pub operator_limits: LimitSettings, // Operator overrides
+    pub pending_limits: Option<LimitSettings>, // Looser player limits waiting out the delay
+    pub pending_effective_at: i64,
+    pub day: i64, // Day index the daily counters belong to
+    pub wagered_today: u64,
+    pub lost_today: u64, // Wagers less payouts today, floored at zero
+    pub last_bet_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
+}
+
+impl PlayerLimits {
+    pub const LEN: usize = 32 + LimitSettings::LEN + LimitSettings::LEN + (1 + LimitSettings::LEN) + 8
+        + 8 + 8 + 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+
+    /// Limits in force, the stricter of the player's and the operator's
+    pub fn effective_limits(&self) -> LimitSettings {
+        self.limits.stricter(&self.operator_limits)
+    }
+
+    /// Apply pending limits whose delay has passed and reset counters on a new day
+    /// Pending limits never carry a self-exclusion, it only ends when its period runs out
+    pub fn refresh(&mut self, now: i64) {
+        if let Some(pending_limits) = self.pending_limits {
+            if now >= self.pending_effective_at {
+                self.limits = LimitSettings {
+                    self_excluded_until: self.limits.self_excluded_until,
+                    ..pending_limits
+                };
+                self.pending_limits = None;
+                self.pending_effective_at = 0;
+            }
+        }
+
+        let today = day_index(now);
+        if today != self.day {
+            self.day = today;
+            self.wagered_today = 0;
+            self.lost_today = 0;
+        }
+    }
+
+    /// Count a payout against today's losses
+    pub fn credit_payout(&mut self, payout: u64, now: i64) {
+        if day_index(now) == self.day {
+            self.lost_today = self.lost_today.saturating_sub(payout);
+        }
+    }
+
+    /// Take a refunded stake back out of today's counters
+    pub fn reverse_wager(&mut self, stake: u64, now: i64) {
+        if day_index(now) == self.day {
+            self.wagered_today = self.wagered_today.saturating_sub(stake);
+            self.lost_today = self.lost_today.saturating_sub(stake);
+        }
+    }
+}
+
+impl Versioned for PlayerLimits {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+#[account]
+pub struct PlayerProfile {
+    pub player: Pubkey,
+    pub total_wagered: u64,
+    pub total_paid_out: u64, // Net of the house edge
+    pub net_pnl: i64, // Paid out less wagered
+    pub bets_placed: u64,
+    pub biggest_multiplier: u64, // Highest multiplier cashed out at, in basis points
+    pub last_active_slot: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub referrer: Pubkey, // Default when the player has no referrer
+    pub vip_level: u8, // VIP tiers reached, 0 when none
+    pub rakeback_pending: u64,
+    pub rakeback_claimed: u64,
+    pub reserved: [u8; ACCOUNT_RESERVED - 32 - 17],
+}
+
+impl PlayerProfile {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+
+    /// Count a placed bet
+    pub fn record_bet(&mut self, amount: u64, slot: u64) {
+        self.total_wagered = self.total_wagered.saturating_add(amount);
+        self.net_pnl = self.net_pnl.saturating_sub(i64::try_from(amount).unwrap_or(i64::MAX));
+        self.bets_placed = self.bets_placed.saturating_add(1);
+        self.last_active_slot = slot;
+    }
+
+    /// Count a winning cashout
+    pub fn record_payout(&mut self, payout: u64, multiplier: u64, slot: u64) {
+        self.total_paid_out = self.total_paid_out.saturating_add(payout);
+        self.net_pnl = self.net_pnl.saturating_add(i64::try_from(payout).unwrap_or(i64::MAX));
+        self.biggest_multiplier = self.biggest_multiplier.max(multiplier);
+        self.last_active_slot = slot;
+    }
+
+    /// Accrue rakeback on a house fee from this player's bet at their VIP tier's rate
+    pub fn accrue_rakeback(&mut self, casino: &mut Casino, fee: u64) -> Result<u64> {
+        let rakeback_bps = vip_tier(&casino.vip_tiers, self.vip_level)
+            .map_or(0, |tier| tier.rakeback_bps);
+        let rakeback = bps_of(fee, rakeback_bps)?;
+
+        self.rakeback_pending = self.rakeback_pending
+            .checked_add(rakeback)
+            .ok_or(CasinoError::MathOverflow)?;
+        casino.rakeback_pool = casino.rakeback_pool
+            .checked_add(rakeback)
+            .ok_or(CasinoError::MathOverflow)?;
+        Ok(rakeback)
+    }
+
+    /// Undo a refunded bet's wager
+    pub fn record_refund(&mut self, amount: u64, slot: u64) {
+        self.total_wagered = self.total_wagered.saturating_sub(amount);
+        self.net_pnl = self.net_pnl.saturating_add(i64::try_from(amount).unwrap_or(i64::MAX));
+        self.bets_placed = self.bets_placed.saturating_sub(1);
+        self.last_active_slot = slot;
+    }
+}
+
+impl Versioned for PlayerProfile {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+#[account]
+pub struct FreeBet {
+    pub id: u64,
+    pub player: Pubkey, // Only this player may redeem it
+    pub amount: u64,
+    pub expiry: i64,
+    pub min_cashout_multiplier: u64, // Basis points
+    pub is_used: bool,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
 }
 
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(mut)]
-    pub casino: Account<'info, Casino>,
-    
-    pub authority: Signer<'info>,
+impl FreeBet {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + ACCOUNT_RESERVED;
+}
+
+impl Versioned for FreeBet {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
 }
 
 #[account]
-pub struct Casino {
-    pub authority: Pubkey,
-    pub house_edge_bps: u16, // Basis points (e.g., 200 = 2%)
-    pub min_bet: u64,
-    pub max_bet: u64,
-    pub is_paused: bool,
+pub struct Referrer {
+    pub referrer: Pubkey,
+    pub tier: u8, // Index into the casino's referral rates
+    pub referred_count: u64,
+    pub pending_rewards: u64, // Accrued but unclaimed, held in the referral pool
+    pub total_earned: u64,
     pub bump: u8,
-    pub current_game_id: u64,
-    pub total_volume: u64,
-    pub total_fees: u64,
-    pub total_staked: u64,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
 }
 
-impl Casino {
-    pub const LEN: usize = 32 + 2 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8;
+impl Referrer {
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+
+    /// Accrue this referrer's share of a referred player's house fee
+    pub fn accrue(&mut self, casino: &Casino, fee: u64) -> Result<u64> {
+        let referral_portion = casino.fee_split.split(fee)?.referral;
+        let rate_bps = casino.referral_rates_bps
+            .get(self.tier as usize)
+            .copied()
+            .unwrap_or_default();
+        let credit = calculate_referral_credit(fee, referral_portion, rate_bps)?;
+
+        self.pending_rewards = self.pending_rewards
+            .checked_add(credit)
+            .ok_or(CasinoError::MathOverflow)?;
+        self.total_earned = self.total_earned
+            .checked_add(credit)
+            .ok_or(CasinoError::MathOverflow)?;
+        Ok(credit)
+    }
+}
+
+impl Versioned for Referrer {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
 }
 
 #[account]
-pub struct Bet {
+pub struct PlayerAttestation {
     pub player: Pubkey,
-    pub amount: u64,
-    pub auto_cashout_multiplier: Option<u64>, // Basis points
-    pub status: BetStatus,
-    pub game_id: u64,
-    pub cashout_multiplier: Option<u64>,
+    pub signer: Pubkey, // Compliance signer that attested, rotating the signer invalidates it
+    pub expiry: i64,
     pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
 }
 
-impl Bet {
-    pub const LEN: usize = 32 + 8 + 9 + 1 + 8 + 9 + 1;
+impl PlayerAttestation {
+    pub const LEN: usize = 32 + 32 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+}
+
+impl Versioned for PlayerAttestation {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
 }
 
 #[account]
-pub struct Game {
-    pub game_id: u64,
-    pub crash_multiplier: u64, // Basis points (e.g., 15000 = 1.5x)
-    pub is_resolved: bool,
+pub struct PendingConfigChange {
+    pub casino: Pubkey,
+    pub params: ConfigParams,
+    pub queued_at: i64,
+    pub eta: i64, // Earliest time the change can be executed
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
 }
 
-impl Game {
-    pub const LEN: usize = 8 + 8 + 1;
+impl PendingConfigChange {
+    pub const LEN: usize = 32 + ConfigParams::LEN + 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+}
+
+impl Versioned for PendingConfigChange {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
 }
 
 #[account]
-pub struct Stake {
-    pub staker: Pubkey,
+pub struct TreasuryWithdrawal {
+    pub index: u64,
+    pub treasurer: Pubkey,
+    pub destination: Pubkey,
     pub amount: u64,
-    pub claimed_rewards: u64,
+    pub epoch: u64,
+    pub timestamp: i64,
     pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; ACCOUNT_RESERVED],
 }
 
-impl Stake {
-    pub const LEN: usize = 32 + 8 + 8 + 1;
+impl TreasuryWithdrawal {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + ACCOUNT_RESERVED;
+}
+
+impl Versioned for TreasuryWithdrawal {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+#[account]
+pub struct RewardStream {
+    pub casino: Pubkey,
+    pub index: u8,
+    pub reward_mint: Pubkey,
+    pub vault: Pubkey,
+    pub emission_rate: u64, // Reward tokens per second across all stakers
+    pub end_time: i64,
+    pub last_update: i64,
+    pub acc_reward_per_weight: u128, // Scaled by REWARD_PRECISION
+    pub bump: u8,
+    pub version: u8,
+    pub undistributed: u64, // Forfeited rewards waiting to be credited to stakers
+    pub reserved: [u8; ACCOUNT_RESERVED - 8],
+}
+
+impl RewardStream {
+    pub const LEN: usize = 32 + 1 + 32 + 32 + 8 + 8 + 8 + 16 + 1 + 1 + ACCOUNT_RESERVED;
+
+    /// Credit emissions since the last update, and any forfeited rewards, to the current total weight
+    pub fn update(&mut self, total_weight: u64, now: i64) -> Result<()> {
+        let mut emitted = calculate_stream_emission(
+            self.emission_rate,
+            self.last_update,
+            self.end_time,
+            now,
+        )?;
+
+        // Forfeited rewards wait for stakers to credit them to
+        if total_weight > 0 {
+            emitted = emitted
+                .checked_add(self.undistributed)
+                .ok_or(CasinoError::MathOverflow)?;
+            self.undistributed = 0;
+        }
+
+        self.acc_reward_per_weight = calculate_reward_per_weight(
+            self.acc_reward_per_weight,
+            emitted,
+            total_weight,
+        )?;
+        self.last_update = self.last_update.max(now.min(self.end_time));
+        Ok(())
+    }
+}
+
+impl Versioned for RewardStream {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn upgrade(&mut self) {
+        self.version = ACCOUNT_VERSION;
+    }
+}
+
+/// A stake's position in one reward stream
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct StreamCheckpoint {
+    pub reward_debt: u128,
+    pub pending_rewards: u64,
+}
+
+impl StreamCheckpoint {
+    pub const LEN: usize = 16 + 8;
+}
+
+/// Load and bring up to date every registered reward stream
+/// Remaining accounts must start with the casino's streams in index order
+pub fn load_reward_streams(
+    casino: &Account<Casino>,
+    remaining_accounts: &[AccountInfo],
+    now: i64,
+) -> Result<Vec<RewardStream>> {
+    let count = casino.reward_stream_count as usize;
+    require!(remaining_accounts.len() >= count, CasinoError::InvalidRewardStream);
+
+    let mut streams = Vec::with_capacity(count);
+    for (index, info) in remaining_accounts[..count].iter().enumerate() {
+        require!(
+            info.owner == &crate::ID && info.is_writable,
+            CasinoError::InvalidRewardStream
+        );
+
+        let mut stream = RewardStream::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(
+            stream.casino == casino.key() && stream.index as usize == index,
+            CasinoError::InvalidRewardStream
+        );
+
+        stream.update(casino.total_weight, now)?;
+        stream.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        streams.push(stream);
+    }
+
+    Ok(streams)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -585,6 +4623,24 @@ pub enum BetStatus {
     Pending,
     CashedOut,
     Lost,
+    Refunded,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    AwaitingRandomness,
+    Resolved,
+    Voided,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Admin,
+    Operator,
+    Guardian,
+    Treasurer,
+    RiskManager,
+    Promo,
 }
 
 #[event]
@@ -607,7 +4663,14 @@ pub struct GameResolved {
 }
 
 #[event]
-pub struct Cashout {
+pub struct CashoutLocked {
+    pub player: Pubkey,
+    pub game_id: u64,
+    pub multiplier: u64,
+}
+
+#[event]
+pub struct CashedOut {
     pub player: Pubkey,
     pub game_id: u64,
     pub multiplier: u64,
@@ -626,8 +4689,258 @@ pub struct LpUnstaked {
     pub amount: u64,
 }
 
+#[event]
+pub struct EmergencyUnstaked {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub forfeited_rewards: u64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub available_at: i64,
+}
+
+#[event]
+pub struct RewardsCompounded {
+    pub staker: Pubkey,
+    pub caller: Pubkey,
+    pub rewards: u64,
+    pub keeper_reward: u64,
+    pub staked_amount: u64,
+}
+
+#[event]
+pub struct StakeLockUpdated {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub lock_end: i64,
+    pub weight: u64,
+}
+
+#[event]
+pub struct StakeTokenized {
+    pub stake: Pubkey,
+    pub staker: Pubkey,
+    pub position_mint: Pubkey,
+}
+
 #[event]
 pub struct RewardsClaimed {
     pub staker: Pubkey,
     pub amount: u64,
 }
+
+#[event]
+pub struct StreamRewardsClaimed {
+    pub staker: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardStreamRegistered {
+    pub index: u8,
+    pub reward_mint: Pubkey,
+    pub emission_rate: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct LiquidityDeposited {
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct LiquidityWithdrawn {
+    pub provider: Pubkey,
+    pub lp_amount: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawalRequested {
+    pub owner: Pubkey,
+    pub lp_amount: u64,
+    pub game_id: u64,
+}
+
+#[event]
+pub struct WithdrawalProcessed {
+    pub owner: Pubkey,
+    pub lp_amount: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawalCancelled {
+    pub owner: Pubkey,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub index: u64,
+    pub treasurer: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct RoleUpdated {
+    pub role: Role,
+    pub previous_holder: Pubkey,
+    pub new_holder: Pubkey,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub params: ConfigParams,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub params: ConfigParams,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub params: ConfigParams,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub before: ConfigParams,
+    pub after: ConfigParams,
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    pub pause_flags: u8,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub version: u8,
+}
+
+#[event]
+pub struct CasinoShutdown {
+    pub game_id: u64,
+    pub refund_liability: u64,
+}
+
+#[event]
+pub struct BetLost {
+    pub player: Pubkey,
+    pub game_id: u64,
+    pub amount: u64,
+    pub crash_multiplier: u64,
+}
+
+#[event]
+pub struct ReferrerRegistered {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ReferralRewardsClaimed {
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RakebackClaimed {
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FreeBetIssued {
+    pub id: u64,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub expiry: i64,
+    pub min_cashout_multiplier: u64,
+}
+
+#[event]
+pub struct FreeBetRedeemed {
+    pub id: u64,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub game_id: u64,
+}
+
+#[event]
+pub struct JackpotTriggered {
+    pub game_id: u64,
+    pub crash_multiplier: u64,
+    pub amount: u64,
+    pub total_wagered: u64,
+}
+
+#[event]
+pub struct JackpotPaid {
+    pub player: Pubkey,
+    pub game_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BetRefunded {
+    pub player: Pubkey,
+    pub game_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RoundVoided {
+    pub game_id: u64,
+    pub request_slot: u64,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub net_flow: i64,
+    pub window_bankroll: u64,
+    pub threshold_bps: u16,
+    pub window_start: i64,
+}
+
+#[event]
+pub struct PlayerLimitsUpdated {
+    pub player: Pubkey,
+    pub limits: LimitSettings,
+    pub operator_limits: LimitSettings,
+    pub pending_limits: Option<LimitSettings>,
+    pub pending_effective_at: i64,
+}
+
+#[event]
+pub struct AttestationSubmitted {
+    pub player: Pubkey,
+    pub signer: Pubkey,
+    pub expiry: i64,
+}
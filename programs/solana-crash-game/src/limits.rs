@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::CasinoError;
+
+/// Delay before a looser player limit takes effect (24 hours)
+pub const LIMIT_LOOSEN_DELAY: i64 = 24 * 60 * 60;
+
+/// Length of the period daily limits are counted over
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Length of a player's self-exclusion, it can't be shortened once started (180 days)
+pub const SELF_EXCLUSION_PERIOD: i64 = 180 * SECONDS_PER_DAY;
+
+/// Responsible-gambling limits, zero caps mean no limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LimitSettings {
+    pub daily_wager_limit: u64,
+    pub daily_loss_limit: u64,
+    pub session_cool_off: i64, // Minimum seconds between bets
+    pub self_excluded_until: i64,
+}
+
+impl LimitSettings {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    /// Check the settings are well formed
+    pub fn validate(&self) -> Result<()> {
+        require!(self.session_cool_off >= 0, CasinoError::InvalidPlayerLimits);
+        Ok(())
+    }
+
+    /// Combine two sets of limits field by field, keeping the stricter of each
+    pub fn stricter(&self, other: &LimitSettings) -> LimitSettings {
+        LimitSettings {
+            daily_wager_limit: stricter_cap(self.daily_wager_limit, other.daily_wager_limit),
+            daily_loss_limit: stricter_cap(self.daily_loss_limit, other.daily_loss_limit),
+            session_cool_off: self.session_cool_off.max(other.session_cool_off),
+            self_excluded_until: self.self_excluded_until.max(other.self_excluded_until),
+        }
+    }
+
+    /// Check a bet of `amount` against the limits and the player's activity today
+    /// The loss limit assumes the bet is lost
+    pub fn check_bet(
+        &self,
+        amount: u64,
+        wagered_today: u64,
+        lost_today: u64,
+        last_bet_at: i64,
+        now: i64,
+    ) -> Result<()> {
+        require!(now >= self.self_excluded_until, CasinoError::SelfExcluded);
+        require!(
+            last_bet_at == 0 || now >= last_bet_at.saturating_add(self.session_cool_off),
+            CasinoError::CoolOffActive
+        );
+
+        if self.daily_wager_limit > 0 {
+            require!(
+                wagered_today.saturating_add(amount) <= self.daily_wager_limit,
+                CasinoError::DailyWagerLimitExceeded
+            );
+        }
+
+        if self.daily_loss_limit > 0 {
+            require!(
+                lost_today.saturating_add(amount) <= self.daily_loss_limit,
+                CasinoError::DailyLossLimitExceeded
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The stricter of two caps where zero means uncapped
+fn stricter_cap(a: u64, b: u64) -> u64 {
+    match (a, b) {
+        (0, cap) | (cap, 0) => cap,
+        _ => a.min(b),
+    }
+}
+
+/// Day index used to reset daily counters
+pub fn day_index(now: i64) -> i64 {
+    now.div_euclid(SECONDS_PER_DAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stricter_treats_zero_as_uncapped() {
+        let player = LimitSettings {
+            daily_wager_limit: 1000,
+            daily_loss_limit: 0,
+            session_cool_off: 60,
+            self_excluded_until: 0,
+        };
+        let operator = LimitSettings {
+            daily_wager_limit: 0,
+            daily_loss_limit: 500,
+            session_cool_off: 30,
+            self_excluded_until: 100,
+        };
+
+        let effective = player.stricter(&operator);
+        assert_eq!(effective.daily_wager_limit, 1000);
+        assert_eq!(effective.daily_loss_limit, 500);
+        assert_eq!(effective.session_cool_off, 60);
+        assert_eq!(effective.self_excluded_until, 100);
+    }
+
+    #[test]
+    fn test_check_bet_limits() {
+        let limits = LimitSettings {
+            daily_wager_limit: 1000,
+            daily_loss_limit: 600,
+            session_cool_off: 60,
+            self_excluded_until: 500,
+        };
+
+        // Self-excluded until 500
+        assert!(limits.check_bet(100, 0, 0, 0, 499).is_err());
+        assert!(limits.check_bet(100, 0, 0, 0, 500).is_ok());
+
+        // Cool-off of 60 seconds after the last bet
+        assert!(limits.check_bet(100, 0, 0, 1000, 1059).is_err());
+        assert!(limits.check_bet(100, 0, 0, 1000, 1060).is_ok());
+
+        // Daily wager and loss caps
+        assert!(limits.check_bet(101, 900, 0, 0, 1000).is_err());
+        assert!(limits.check_bet(101, 0, 500, 0, 1000).is_err());
+        assert!(limits.check_bet(100, 900, 500, 0, 1000).is_ok());
+    }
+
+    #[test]
+    fn test_day_index() {
+        assert_eq!(day_index(0), 0);
+        assert_eq!(day_index(SECONDS_PER_DAY - 1), 0);
+        assert_eq!(day_index(SECONDS_PER_DAY), 1);
+    }
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+use crate::crash::MAX_CRASH_MULTIPLIER;
+use crate::errors::CasinoError;
+
+/// LP shares of the first deposit locked for good, so a donation can't inflate the share price
+pub const MINIMUM_LIQUIDITY: u64 = 1000;
+
+/// Calculate LP tokens to mint for a deposit at current vault NAV
+/// The first deposit (empty supply) is minted 1:1 with the bet token and must exceed the locked shares
+pub fn calculate_lp_to_mint(
+    deposit_amount: u64,
+    vault_balance: u64,
+    lp_supply: u64,
+) -> Result<u64> {
+    if lp_supply == 0 {
+        require!(deposit_amount > MINIMUM_LIQUIDITY, CasinoError::InvalidLiquidityAmount);
+        return Ok(deposit_amount);
+    }
+
+    // Outstanding shares of an empty vault have no price to mint against
+    require!(vault_balance > 0, CasinoError::EmptyBankroll);
+
+    let minted = (deposit_amount as u128)
+        .checked_mul(lp_supply as u128)
+        .and_then(|x| x.checked_div(vault_balance as u128))
+        .ok_or(CasinoError::MathOverflow)?;
+
+    u64::try_from(minted).map_err(|_| CasinoError::MathOverflow.into())
+}
+
+/// Calculate bet tokens owed for burning LP tokens at current vault NAV
+pub fn calculate_withdraw_amount(
+    lp_amount: u64,
+    vault_balance: u64,
+    lp_supply: u64,
+) -> Result<u64> {
+    require!(lp_amount <= lp_supply, CasinoError::InsufficientLiquidity);
+
+    let amount = (lp_amount as u128)
+        .checked_mul(vault_balance as u128)
+        .and_then(|x| x.checked_div(lp_supply as u128))
+        .ok_or(CasinoError::MathOverflow)?;
+
+    u64::try_from(amount).map_err(|_| CasinoError::MathOverflow.into())
+}
+
+/// Calculate the most a bet can pay out, used as its bankroll exposure
+/// Bets with auto-cashout are capped at their target, others at the max crash point
+pub fn calculate_bet_exposure(amount: u64, auto_cashout_multiplier: Option<u64>) -> Result<u64> {
+    let multiplier = auto_cashout_multiplier
+        .unwrap_or(MAX_CRASH_MULTIPLIER)
+        .min(MAX_CRASH_MULTIPLIER);
+
+    amount
+        .checked_mul(multiplier)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow.into())
+}
+
+/// Vault balance not committed to the outstanding exposure of pending bets
+pub fn calculate_free_balance(vault_balance: u64, round_exposure: u64) -> u64 {
+    vault_balance.saturating_sub(round_exposure)
+}
+
+/// Whether house losses in a window exceed `threshold_bps` of the bankroll it started with
+/// `net_flow` is wagers in less payouts out, a threshold of 0 disables the check
+pub fn is_drawdown_exceeded(net_flow: i64, window_bankroll: u64, threshold_bps: u16) -> bool {
+    if threshold_bps == 0 || net_flow >= 0 {
+        return false;
+    }
+
+    let loss = net_flow.unsigned_abs() as u128;
+    let limit = (window_bankroll as u128) * (threshold_bps as u128) / 10000;
+    loss > limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_deposit_mints_one_to_one() {
+        let minted = calculate_lp_to_mint(5000, 0, 0).unwrap();
+        assert_eq!(minted, 5000);
+
+        // Too small to cover the locked shares
+        assert!(calculate_lp_to_mint(MINIMUM_LIQUIDITY, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_deposit_rejected_into_empty_vault() {
+        // Shares are outstanding but the bankroll was wiped out
+        assert!(calculate_lp_to_mint(500, 0, 1000).is_err());
+    }
+
+    #[test]
+    fn test_deposit_priced_at_nav() {
+        // Vault grew to 2000 backing 1000 LP, so NAV is 2.0
+        let minted = calculate_lp_to_mint(500, 2000, 1000).unwrap();
+        assert_eq!(minted, 250);
+    }
+
+    #[test]
+    fn test_withdraw_priced_at_nav() {
+        // Vault lost half its value, 250 of 1000 LP redeems a quarter of 500
+        let amount = calculate_withdraw_amount(250, 500, 1000).unwrap();
+        assert_eq!(amount, 125);
+
+        assert!(calculate_withdraw_amount(1001, 500, 1000).is_err());
+    }
+
+    #[test]
+    fn test_bet_exposure() {
+        // 2x auto-cashout risks twice the stake
+        let exposure = calculate_bet_exposure(100, Some(20000)).unwrap();
+        assert_eq!(exposure, 200);
+
+        // No auto-cashout risks the max crash point
+        let exposure = calculate_bet_exposure(100, None).unwrap();
+        assert_eq!(exposure, 10000);
+    }
+
+    #[test]
+    fn test_free_balance() {
+        assert_eq!(calculate_free_balance(1000, 400), 600);
+        assert_eq!(calculate_free_balance(1000, 1500), 0);
+    }
+
+    #[test]
+    fn test_drawdown_threshold() {
+        // 20% of a 10000 bankroll
+        assert!(!is_drawdown_exceeded(-2000, 10000, 2000));
+        assert!(is_drawdown_exceeded(-2001, 10000, 2000));
+
+        // Net winnings and a disabled breaker never trip
+        assert!(!is_drawdown_exceeded(5000, 10000, 2000));
+        assert!(!is_drawdown_exceeded(-9000, 10000, 0));
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::errors::CasinoError;
+
+/// Layout version written to every account created or migrated by this program
+pub const ACCOUNT_VERSION: u8 = 3;
+
+/// Bytes reserved at the end of every account, new fields are carved out of this
+pub const ACCOUNT_RESERVED: usize = 64;
+
+/// Accounts that carry a layout version and can be upgraded in place
+pub trait Versioned {
+    fn version(&self) -> u8;
+
+    /// Fill fields added since the account's version and stamp `ACCOUNT_VERSION`
+    fn upgrade(&mut self);
+}
+
+/// Grow an account to `new_len`, topping up rent from `payer` and zero-filling the new bytes
+pub fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let rent_due = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+        system_program::transfer(cpi_ctx, rent_due)?;
+    }
+
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+/// Realloc a program account to `space` and rewrite it in the current layout
+/// Older layouts are a prefix of the current one, so the zero-filled tail reads as new fields
+pub fn upgrade_account<'info, T>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()>
+where
+    T: AccountSerialize + AccountDeserialize + Versioned,
+{
+    require!(account.owner == &crate::ID, CasinoError::InvalidMigration);
+    realloc_account(account, payer, system_program, space)?;
+
+    let mut upgraded = {
+        let data = account.try_borrow_data()?;
+        T::try_deserialize(&mut &data[..])?
+    };
+    require!(upgraded.version() < ACCOUNT_VERSION, CasinoError::AlreadyMigrated);
+    upgraded.upgrade();
+
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    upgraded.try_serialize(&mut writer)?;
+
+    Ok(())
+}
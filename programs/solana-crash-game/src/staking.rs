@@ -2,6 +2,21 @@ use anchor_lang::prelude::*;
 
 use crate::errors::CasinoError;
 
+/// Shortest lock a staker can choose (1 week)
+pub const MIN_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;
+
+/// Longest lock a staker can choose (1 year)
+pub const MAX_LOCK_DURATION: i64 = 365 * 24 * 60 * 60;
+
+/// Extra reward weight for a full-length lock in basis points (+150% = 2.5x)
+pub const MAX_LOCK_BOOST_BPS: u64 = 15000;
+
+/// Fixed-point scale for reward-per-weight accumulators
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Most extra reward streams a casino can register
+pub const MAX_REWARD_STREAMS: usize = 4;
+
 /// Calculate staker's share of total fees
 pub fn calculate_staker_share(
     staker_amount: u64,
@@ -32,6 +47,91 @@ pub fn calculate_pending_rewards(
         .ok_or(CasinoError::MathOverflow.into())
 }
 
+/// Calculate the boost multiplier (basis points) for the time left on a lock
+/// Decays linearly from 2.5x at a one-year lock to 1x when the lock ends
+pub fn calculate_lock_boost(lock_end: i64, now: i64) -> u64 {
+    let remaining = lock_end.saturating_sub(now).clamp(0, MAX_LOCK_DURATION) as u64;
+
+    10000 + MAX_LOCK_BOOST_BPS * remaining / MAX_LOCK_DURATION as u64
+}
+
+/// Calculate a stake's boosted reward weight
+pub fn calculate_stake_weight(amount: u64, lock_end: i64, now: i64) -> Result<u64> {
+    let weight = (amount as u128)
+        .checked_mul(calculate_lock_boost(lock_end, now) as u128)
+        .and_then(|x| x.checked_div(10000))
+        .ok_or(CasinoError::MathOverflow)?;
+
+    u64::try_from(weight).map_err(|_| CasinoError::MathOverflow.into())
+}
+
+/// Split rewards earned at a boosted `weight` into the base share of `amount` and the lock boost
+pub fn split_boost_rewards(rewards: u64, amount: u64, weight: u64) -> Result<(u64, u64)> {
+    if weight <= amount {
+        return Ok((rewards, 0));
+    }
+
+    let base = (rewards as u128)
+        .checked_mul(amount as u128)
+        .and_then(|x| x.checked_div(weight as u128))
+        .ok_or(CasinoError::MathOverflow)?;
+    let base = u64::try_from(base).map_err(|_| CasinoError::MathOverflow)?;
+
+    Ok((base, rewards - base))
+}
+
+/// Add a reward to a reward-per-weight accumulator
+pub fn calculate_reward_per_weight(
+    reward_per_weight: u128,
+    reward: u64,
+    total_weight: u64,
+) -> Result<u128> {
+    if total_weight == 0 {
+        return Ok(reward_per_weight);
+    }
+
+    (reward as u128)
+        .checked_mul(REWARD_PRECISION)
+        .and_then(|x| x.checked_div(total_weight as u128))
+        .and_then(|x| x.checked_add(reward_per_weight))
+        .ok_or(CasinoError::MathOverflow.into())
+}
+
+/// Calculate a weight's total entitlement from an accumulator
+pub fn calculate_reward_debt(weight: u64, reward_per_weight: u128) -> Result<u128> {
+    (weight as u128)
+        .checked_mul(reward_per_weight)
+        .and_then(|x| x.checked_div(REWARD_PRECISION))
+        .ok_or(CasinoError::MathOverflow.into())
+}
+
+/// Calculate rewards accrued since the last checkpoint
+pub fn calculate_accrued_rewards(
+    weight: u64,
+    reward_per_weight: u128,
+    reward_debt: u128,
+) -> Result<u64> {
+    let accrued = calculate_reward_debt(weight, reward_per_weight)?
+        .checked_sub(reward_debt)
+        .ok_or(CasinoError::MathOverflow)?;
+
+    u64::try_from(accrued).map_err(|_| CasinoError::MathOverflow.into())
+}
+
+/// Calculate tokens a stream emitted since its last update, stopping at its end time
+pub fn calculate_stream_emission(
+    emission_rate: u64,
+    last_update: i64,
+    end_time: i64,
+    now: i64,
+) -> Result<u64> {
+    let elapsed = now.min(end_time).saturating_sub(last_update).max(0) as u64;
+
+    emission_rate
+        .checked_mul(elapsed)
+        .ok_or(CasinoError::MathOverflow.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,4 +152,46 @@ mod tests {
         let pending = calculate_pending_rewards(100, 200, 1000, 300).unwrap();
         assert_eq!(pending, 200); // 500 total - 300 claimed = 200 pending
     }
+
+    #[test]
+    fn test_lock_boost_decays_linearly() {
+        let now = 1_000_000;
+        assert_eq!(calculate_lock_boost(now + MAX_LOCK_DURATION, now), 25000);
+        assert_eq!(calculate_lock_boost(now + MAX_LOCK_DURATION / 2, now), 17500);
+        assert_eq!(calculate_lock_boost(now, now), 10000);
+        assert_eq!(calculate_lock_boost(0, now), 10000);
+
+        let weight = calculate_stake_weight(100, now + MAX_LOCK_DURATION, now).unwrap();
+        assert_eq!(weight, 250);
+    }
+
+    #[test]
+    fn test_rewards_follow_weight() {
+        // 300 total weight, 600 fees -> 2 per unit of weight
+        let acc = calculate_reward_per_weight(0, 600, 300).unwrap();
+        assert_eq!(calculate_accrued_rewards(250, acc, 0).unwrap(), 500);
+        assert_eq!(calculate_accrued_rewards(50, acc, 0).unwrap(), 100);
+
+        // Debt checkpoint excludes rewards from before the stake joined
+        let debt = calculate_reward_debt(50, acc).unwrap();
+        let acc = calculate_reward_per_weight(acc, 300, 300).unwrap();
+        assert_eq!(calculate_accrued_rewards(50, acc, debt).unwrap(), 50);
+    }
+
+    #[test]
+    fn test_boost_rewards_split_from_base() {
+        // A 2.5x weight earns 1000, 400 of it on the staked amount and 600 from the lock
+        assert_eq!(split_boost_rewards(1000, 100, 250).unwrap(), (400, 600));
+
+        // Unlocked stakes earn no boost
+        assert_eq!(split_boost_rewards(1000, 100, 100).unwrap(), (1000, 0));
+        assert_eq!(split_boost_rewards(1000, 0, 0).unwrap(), (1000, 0));
+    }
+
+    #[test]
+    fn test_stream_emission_stops_at_end() {
+        assert_eq!(calculate_stream_emission(10, 100, 1000, 150).unwrap(), 500);
+        assert_eq!(calculate_stream_emission(10, 900, 1000, 2000).unwrap(), 1000);
+        assert_eq!(calculate_stream_emission(10, 1000, 1000, 2000).unwrap(), 0);
+    }
 }
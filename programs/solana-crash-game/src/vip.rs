@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::CasinoError;
+
+/// Number of VIP tiers a casino can define
+pub const VIP_TIER_COUNT: usize = 4;
+
+/// Highest share of a house fee returned as rakeback (50%)
+pub const MAX_RAKEBACK_BPS: u16 = 5000;
+
+/// A VIP tier reached at a lifetime wagered volume, unset tiers have zero volume
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VipTier {
+    pub min_wagered: u64,
+    pub rakeback_bps: u16, // Share of the house fee on the player's bets returned to them
+    pub max_bet: u64, // Raised bet limit, 0 keeps the casino's
+}
+
+impl VipTier {
+    pub const LEN: usize = 8 + 2 + 8;
+
+    pub fn is_set(&self) -> bool {
+        self.min_wagered > 0
+    }
+}
+
+/// Check set tiers come first in strictly ascending volume with bounded perks
+pub fn validate_vip_tiers(tiers: &[VipTier; VIP_TIER_COUNT], max_payout: u64) -> Result<()> {
+    let mut previous: Option<&VipTier> = None;
+    let mut unset_seen = false;
+
+    for tier in tiers {
+        if !tier.is_set() {
+            require!(*tier == VipTier::default(), CasinoError::InvalidVipTiers);
+            unset_seen = true;
+            continue;
+        }
+
+        require!(!unset_seen, CasinoError::InvalidVipTiers);
+        require!(tier.rakeback_bps <= MAX_RAKEBACK_BPS, CasinoError::InvalidVipTiers);
+        require!(tier.max_bet <= max_payout, CasinoError::InvalidVipTiers);
+        if let Some(previous) = previous {
+            require!(tier.min_wagered > previous.min_wagered, CasinoError::InvalidVipTiers);
+        }
+        previous = Some(tier);
+    }
+
+    Ok(())
+}
+
+/// VIP level reached with `total_wagered`, the number of tiers passed (0 means none)
+pub fn vip_level(tiers: &[VipTier; VIP_TIER_COUNT], total_wagered: u64) -> u8 {
+    tiers
+        .iter()
+        .take_while(|tier| tier.is_set() && total_wagered >= tier.min_wagered)
+        .count() as u8
+}
+
+/// The tier behind a VIP level, none for level 0
+pub fn vip_tier(tiers: &[VipTier; VIP_TIER_COUNT], level: u8) -> Option<&VipTier> {
+    (level as usize)
+        .checked_sub(1)
+        .and_then(|index| tiers.get(index))
+        .filter(|tier| tier.is_set())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiers() -> [VipTier; VIP_TIER_COUNT] {
+        [
+            VipTier { min_wagered: 1000, rakeback_bps: 500, max_bet: 0 },
+            VipTier { min_wagered: 10000, rakeback_bps: 1000, max_bet: 5000 },
+            VipTier::default(),
+            VipTier::default(),
+        ]
+    }
+
+    #[test]
+    fn test_vip_tier_validation() {
+        assert!(validate_vip_tiers(&tiers(), 100000).is_ok());
+        assert!(validate_vip_tiers(&[VipTier::default(); VIP_TIER_COUNT], 100000).is_ok());
+
+        // Volumes must ascend
+        let mut unordered = tiers();
+        unordered[1].min_wagered = 1000;
+        assert!(validate_vip_tiers(&unordered, 100000).is_err());
+
+        // No set tier after an unset one
+        let mut gap = tiers();
+        gap[3] = VipTier { min_wagered: 50000, rakeback_bps: 0, max_bet: 0 };
+        assert!(validate_vip_tiers(&gap, 100000).is_err());
+
+        // Perks are bounded
+        let mut generous = tiers();
+        generous[0].rakeback_bps = MAX_RAKEBACK_BPS + 1;
+        assert!(validate_vip_tiers(&generous, 100000).is_err());
+        assert!(validate_vip_tiers(&tiers(), 4000).is_err());
+    }
+
+    #[test]
+    fn test_vip_level() {
+        assert_eq!(vip_level(&tiers(), 999), 0);
+        assert_eq!(vip_level(&tiers(), 1000), 1);
+        assert_eq!(vip_level(&tiers(), 1_000_000), 2);
+
+        assert!(vip_tier(&tiers(), 0).is_none());
+        assert_eq!(vip_tier(&tiers(), 2).unwrap().rakeback_bps, 1000);
+        assert!(vip_tier(&tiers(), 3).is_none());
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::CasinoError;
+
 /// Request VRF randomness from Switchboard
 /// Note: This is a placeholder - full implementation requires Switchboard VRF setup
 pub fn request_vrf() -> Result<()> {
@@ -36,12 +38,17 @@ pub fn verify_vrf_result(
 
 /// Extract random bytes from VRF result
 pub fn extract_vrf_bytes(vrf_result: &[u8]) -> Result<[u8; 32]> {
-    require!(vrf_result.len() >= 32, ErrorCode::InvalidVrfResult);
+    require!(vrf_result.len() >= 32, CasinoError::InvalidVrfResult);
     
     let mut bytes = [0u8; 32];
     bytes.copy_from_slice(&vrf_result[0..32]);
     Ok(bytes)
 }
 
+/// Whether a randomness request made at `request_slot` has gone unfulfilled for too long
+pub fn is_randomness_expired(request_slot: u64, timeout_slots: u64, current_slot: u64) -> bool {
+    current_slot >= request_slot.saturating_add(timeout_slots)
+}
+
 // Note: For production, you may want to use ORAO VRF or Chainlink
 // if Switchboard setup is complex. The interface can be abstracted.
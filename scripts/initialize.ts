@@ -42,7 +42,7 @@ async function main() {
     console.log("  House Edge:", casinoAccount.houseEdgeBps.toNumber(), "bps (", (casinoAccount.houseEdgeBps.toNumber() / 100).toFixed(2), "%)");
     console.log("  Min Bet:", casinoAccount.minBet.toNumber() / LAMPORTS_PER_SOL, "SOL");
     console.log("  Max Bet:", casinoAccount.maxBet.toNumber() / LAMPORTS_PER_SOL, "SOL");
-    console.log("  Paused:", casinoAccount.isPaused);
+    console.log("  Pause Flags:", casinoAccount.pauseFlags);
   } catch (error) {
     console.error("❌ Error initializing casino:", error);
     process.exit(1);
@@ -0,0 +1,71 @@
+import { BN } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { CONFIG_CHANGE_DELAY, Env, TOKEN, configParams } from "./helpers";
+
+describe("config", () => {
+  it("Applies economic changes only after the timelock", async () => {
+    const env = await Env.create();
+    const params = configParams({ houseEdgeBps: 300 });
+
+    await env.fails(
+      [await env.program.methods.updateConfig(params).accounts(env.configAccounts()).instruction()],
+      [],
+      "ConfigTimelocked"
+    );
+
+    await env.send([await env.queueIx(params)]);
+    await env.fails([await env.executeIx()], [], "ConfigChangeNotReady");
+    await env.advance(CONFIG_CHANGE_DELAY - 1);
+    await env.fails([await env.executeIx()], [], "ConfigChangeNotReady");
+    expect((await env.fetchCasino()).houseEdgeBps).to.equal(200);
+
+    await env.advance(1);
+    await env.send([await env.executeIx()]);
+    expect((await env.fetchCasino()).houseEdgeBps).to.equal(300);
+    expect(await env.exists(env.configChange())).to.be.false;
+  });
+
+  it("Keeps VIP bet limits within the payout cap", async () => {
+    const env = await Env.create();
+    const noTier = { minWagered: new BN(0), rakebackBps: 0, maxBet: new BN(0) };
+    const tiers = [
+      { minWagered: new BN(10 * TOKEN), rakebackBps: 1000, maxBet: new BN(500 * TOKEN) },
+      noTier,
+      noTier,
+      noTier,
+    ];
+    await env.send([await env.program.methods.setVipTiers(tiers).accounts(env.configAccounts()).instruction()]);
+
+    await env.fails(
+      [
+        await env.program.methods
+          .updateConfig(configParams({ maxPayout: new BN(200 * TOKEN) }))
+          .accounts(env.configAccounts())
+          .instruction(),
+      ],
+      [],
+      "InvalidVipTiers"
+    );
+    await env.send([
+      await env.program.methods
+        .updateConfig(configParams({ maxPayout: new BN(500 * TOKEN) }))
+        .accounts(env.configAccounts())
+        .instruction(),
+    ]);
+    expect((await env.fetchCasino()).maxPayout.toNumber()).to.equal(500 * TOKEN);
+
+    // The raised limit applies once the tier is reached
+    await env.mintTo(env.mint, env.vault, 1000 * TOKEN);
+    const [player, tokenAccount] = await env.player(1000 * TOKEN);
+    let id = await env.startRound();
+    await env.fails(
+      [await env.placeBetIx(player.publicKey, tokenAccount, id, 200 * TOKEN)],
+      [player],
+      "InvalidBetAmount"
+    );
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player]);
+    await env.resolve(0x00);
+    id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 200 * TOKEN)], [player]);
+  });
+});
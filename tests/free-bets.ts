@@ -0,0 +1,78 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import { Env, TOKEN } from "./helpers";
+
+// Issue a voucher to `player`, returns its id
+async function issueFreeBet(env: Env, player: PublicKey, amount: number, expiry: number, minCashout: number) {
+  const id = (await env.fetchCasino()).freeBetCount.toNumber();
+  await env.send([
+    await env.program.methods
+      .issueFreeBet(new BN(amount), new BN(expiry), new BN(minCashout))
+      .accounts({
+        casino: env.casino,
+        freeBet: env.freeBetPda(id),
+        player,
+        authority: env.admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction(),
+  ]);
+  return id;
+}
+
+async function placeFreeBetIx(env: Env, player: PublicKey, voucher: number, id: number, auto: number | null) {
+  return env.program.methods
+    .placeFreeBet(auto === null ? null : new BN(auto))
+    .accounts({
+      casino: env.casino,
+      freeBet: env.freeBetPda(voucher),
+      game: env.gamePda(id),
+      bet: env.betPda(player, id),
+      playerLimits: env.limitsPda(player),
+      playerAttestation: null,
+      player,
+      vault: env.vault,
+      systemProgram: SystemProgram.programId,
+    })
+    .instruction();
+}
+
+describe("free bets", () => {
+  it("Pays a voucher's profit once", async () => {
+    const env = await Env.create();
+    await env.mintTo(env.mint, env.vault, 1000 * TOKEN);
+    const [player, tokenAccount] = await env.player();
+    const voucher = await issueFreeBet(env, player.publicKey, 10 * TOKEN, env.now + 3600, 20000);
+    let id = await env.startRound();
+
+    await env.fails(
+      [await placeFreeBetIx(env, player.publicKey, voucher, id, 15000)],
+      [player],
+      "FreeBetMultiplierTooLow"
+    );
+    await env.send([await placeFreeBetIx(env, player.publicKey, voucher, id, 30000)], [player]);
+
+    // Nothing is staked from the vault or counted as wagered
+    const game = await env.program.account.game.fetch(env.gamePda(id));
+    expect(game.totalWagered.toNumber()).to.equal(0);
+    expect(await env.balance(env.vault)).to.equal(1000 * TOKEN);
+
+    await env.resolve(0xff);
+    await env.send([await env.settleIx(player.publicKey, tokenAccount, id)]);
+    // 20 profit at 3x less the 2% fee on it
+    expect(await env.balance(tokenAccount)).to.equal(19_600_000);
+
+    id = await env.startRound();
+    await env.fails([await placeFreeBetIx(env, player.publicKey, voucher, id, 30000)], [player], "FreeBetUsed");
+  });
+
+  it("Rejects an expired voucher", async () => {
+    const env = await Env.create();
+    const [player] = await env.player();
+    const voucher = await issueFreeBet(env, player.publicKey, 10 * TOKEN, env.now + 60, 20000);
+    const id = await env.startRound();
+    await env.advance(60);
+    await env.fails([await placeFreeBetIx(env, player.publicKey, voucher, id, null)], [player], "FreeBetExpired");
+  });
+});
@@ -0,0 +1,635 @@
+import { BN, Program } from "@coral-xyz/anchor";
+import { BankrunProvider } from "anchor-bankrun";
+import { Clock, ProgramTestContext, startAnchor } from "solana-bankrun";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  ACCOUNT_SIZE,
+  AccountLayout,
+  MINT_SIZE,
+  MintLayout,
+  TOKEN_PROGRAM_ID,
+  createInitializeAccount3Instruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { IDL, SolanaCrashGame } from "../target/types/solana_crash_game";
+import idl from "../target/idl/solana_crash_game.json";
+
+// One whole bet token, the test mint has 6 decimals
+export const TOKEN = 1_000_000;
+
+// Mirrors of the program constants the tests depend on
+export const PAUSE_BETS = 1 << 0;
+export const PAUSE_WITHDRAWALS = 1 << 4;
+export const CONFIG_CHANGE_DELAY = 2 * 24 * 60 * 60;
+export const MIN_LOCK_DURATION = 7 * 24 * 60 * 60;
+export const DEFAULT_VRF_TIMEOUT_SLOTS = 1500;
+export const LIMIT_LOOSEN_DELAY = 24 * 60 * 60;
+export const SELF_EXCLUSION_PERIOD = 180 * 24 * 60 * 60;
+
+export type FeeSplit = {
+  stakerBps: number;
+  treasuryBps: number;
+  referralBps: number;
+  jackpotBps: number;
+};
+
+export type LimitSettings = {
+  dailyWagerLimit: BN;
+  dailyLossLimit: BN;
+  sessionCoolOff: BN;
+  selfExcludedUntil: BN;
+};
+
+// Config params with every field unset, overridden by `params`
+export function configParams(params: Record<string, unknown> = {}) {
+  return {
+    houseEdgeBps: null,
+    minBet: null,
+    maxBet: null,
+    feeSplit: null,
+    curveScale: null,
+    maxPayout: null,
+    maxExposureBps: null,
+    bettingWindow: null,
+    keeperRewardBps: null,
+    ...params,
+  };
+}
+
+export function limitSettings(settings: Partial<LimitSettings> = {}): LimitSettings {
+  return {
+    dailyWagerLimit: new BN(0),
+    dailyLossLimit: new BN(0),
+    sessionCoolOff: new BN(0),
+    selfExcludedUntil: new BN(0),
+    ...settings,
+  };
+}
+
+// A fresh in-process chain with an initialized casino, vault and fee vault.
+// The clock is driven by `now` so time-gated flows can be tested directly.
+export class Env {
+  now = 1_700_000_000;
+  mint = PublicKey.default;
+  readonly admin: Keypair;
+  readonly casino: PublicKey;
+  readonly vault: PublicKey;
+  readonly feeVault: PublicKey;
+
+  private constructor(
+    readonly context: ProgramTestContext,
+    readonly provider: BankrunProvider,
+    readonly program: Program<SolanaCrashGame>
+  ) {
+    this.admin = context.payer;
+    this.casino = this.pda([Buffer.from("casino")]);
+    this.vault = this.pda([Buffer.from("vault"), this.casino.toBuffer()]);
+    this.feeVault = this.pda([Buffer.from("fee_vault"), this.casino.toBuffer()]);
+  }
+
+  static async create(): Promise<Env> {
+    const context = await startAnchor(".", [], []);
+    const provider = new BankrunProvider(context);
+    const program = new Program<SolanaCrashGame>(IDL, new PublicKey(idl.metadata.address), provider);
+    const env = new Env(context, provider, program);
+    await env.tick();
+    env.mint = await env.createMint();
+    await env.initialize();
+    return env;
+  }
+
+  // Chain
+
+  // Move to a later slot with the clock at `now`, so repeated transactions stay distinct
+  async tick(slots = 1) {
+    const clock = await this.context.banksClient.getClock();
+    const slot = clock.slot + BigInt(slots);
+    this.context.warpToSlot(slot);
+    this.context.setClock(
+      new Clock(slot, clock.epochStartTimestamp, clock.epoch, clock.leaderScheduleEpoch, BigInt(this.now))
+    );
+  }
+
+  async advance(secs: number) {
+    this.now += secs;
+    await this.tick();
+  }
+
+  async advanceSlots(slots: number) {
+    await this.tick(slots);
+  }
+
+  async send(ixs: TransactionInstruction[], signers: Keypair[] = []) {
+    await this.tick();
+    await this.provider.sendAndConfirm(new Transaction().add(...ixs), signers);
+  }
+
+  // Send and expect the program to reject with the named error
+  async fails(ixs: TransactionInstruction[], signers: Keypair[], name: string) {
+    const error = this.program.idl.errors.find((e) => e.name === name);
+    expect(error, `unknown error ${name}`).to.not.be.undefined;
+    try {
+      await this.send(ixs, signers);
+    } catch (err) {
+      const logs = (err as { logs?: string[] }).logs ?? [];
+      const text = `${err}\n${logs.join("\n")}`;
+      const hex = `0x${error!.code.toString(16)}`;
+      expect(text.includes(hex) || text.includes(name), `expected ${name}, got ${text}`).to.be.true;
+      return;
+    }
+    expect.fail(`expected ${name} but the transaction succeeded`);
+  }
+
+  pda(seeds: Buffer[]): PublicKey {
+    return PublicKey.findProgramAddressSync(seeds, this.program.programId)[0];
+  }
+
+  async exists(key: PublicKey): Promise<boolean> {
+    return (await this.context.banksClient.getAccount(key)) !== null;
+  }
+
+  // Tokens
+
+  async createMint(): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    const rent = await this.context.banksClient.getRent();
+    await this.send(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: this.admin.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports: Number(rent.minimumBalance(BigInt(MINT_SIZE))),
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(mint.publicKey, 6, this.admin.publicKey, null),
+      ],
+      [mint]
+    );
+    return mint.publicKey;
+  }
+
+  async tokenAccount(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
+    const account = Keypair.generate();
+    const rent = await this.context.banksClient.getRent();
+    await this.send(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: this.admin.publicKey,
+          newAccountPubkey: account.publicKey,
+          lamports: Number(rent.minimumBalance(BigInt(ACCOUNT_SIZE))),
+          space: ACCOUNT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeAccount3Instruction(account.publicKey, mint, owner),
+      ],
+      [account]
+    );
+    return account.publicKey;
+  }
+
+  async mintTo(mint: PublicKey, dest: PublicKey, amount: number) {
+    await this.send([createMintToInstruction(mint, dest, this.admin.publicKey, amount)]);
+  }
+
+  async balance(account: PublicKey): Promise<number> {
+    const info = await this.context.banksClient.getAccount(account);
+    return Number(AccountLayout.decode(info!.data).amount);
+  }
+
+  async supply(mint: PublicKey): Promise<number> {
+    const info = await this.context.banksClient.getAccount(mint);
+    return Number(MintLayout.decode(info!.data).supply);
+  }
+
+  // A new funded player and their bet token account holding `amount`
+  async player(amount = 0): Promise<[Keypair, PublicKey]> {
+    const player = Keypair.generate();
+    await this.send([
+      SystemProgram.transfer({
+        fromPubkey: this.admin.publicKey,
+        toPubkey: player.publicKey,
+        lamports: 10 * LAMPORTS_PER_SOL,
+      }),
+    ]);
+    const tokenAccount = await this.tokenAccount(this.mint, player.publicKey);
+    if (amount > 0) {
+      await this.mintTo(this.mint, tokenAccount, amount);
+    }
+    return [player, tokenAccount];
+  }
+
+  // Addresses
+
+  gamePda(id: number): PublicKey {
+    return this.pda([Buffer.from("game"), this.casino.toBuffer(), le(id)]);
+  }
+
+  betPda(player: PublicKey, id: number): PublicKey {
+    return this.pda([Buffer.from("bet"), this.casino.toBuffer(), player.toBuffer(), le(id)]);
+  }
+
+  limitsPda(player: PublicKey): PublicKey {
+    return this.pda([Buffer.from("player_limits"), this.casino.toBuffer(), player.toBuffer()]);
+  }
+
+  profilePda(player: PublicKey): PublicKey {
+    return this.pda([Buffer.from("player_profile"), this.casino.toBuffer(), player.toBuffer()]);
+  }
+
+  lpMint(): PublicKey {
+    return this.pda([Buffer.from("lp_mint"), this.casino.toBuffer()]);
+  }
+
+  lpEscrow(): PublicKey {
+    return this.pda([Buffer.from("lp_escrow"), this.casino.toBuffer()]);
+  }
+
+  stakingVault(): PublicKey {
+    return this.pda([Buffer.from("staking_vault"), this.casino.toBuffer()]);
+  }
+
+  stakePda(staker: PublicKey): PublicKey {
+    return this.pda([Buffer.from("stake"), this.casino.toBuffer(), staker.toBuffer()]);
+  }
+
+  configChange(): PublicKey {
+    return this.pda([Buffer.from("config_change"), this.casino.toBuffer()]);
+  }
+
+  referrerPda(referrer: PublicKey): PublicKey {
+    return this.pda([Buffer.from("referrer"), this.casino.toBuffer(), referrer.toBuffer()]);
+  }
+
+  freeBetPda(id: number): PublicKey {
+    return this.pda([Buffer.from("free_bet"), this.casino.toBuffer(), le(id)]);
+  }
+
+  // Casino setup and admin
+
+  private async initialize() {
+    await this.send([
+      await this.program.methods
+        .initialize(200, new BN(TOKEN), new BN(100 * TOKEN))
+        .accounts({
+          casino: this.casino,
+          authority: this.admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .instruction(),
+    ]);
+    await this.send([
+      await this.program.methods
+        .initializeVault()
+        .accounts({
+          casino: this.casino,
+          vault: this.vault,
+          betMint: this.mint,
+          authority: this.admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .instruction(),
+    ]);
+    await this.send([
+      await this.program.methods
+        .initializeFeeVault()
+        .accounts({
+          casino: this.casino,
+          feeVault: this.feeVault,
+          vault: this.vault,
+          betMint: this.mint,
+          authority: this.admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .instruction(),
+    ]);
+  }
+
+  async fetchCasino() {
+    return this.program.account.casino.fetch(this.casino);
+  }
+
+  // Accounts for the admin instructions that take `UpdateConfig`
+  configAccounts() {
+    return { casino: this.casino, authority: this.admin.publicKey };
+  }
+
+  async queueIx(params: ReturnType<typeof configParams>) {
+    return this.program.methods
+      .queueConfigChange(params)
+      .accounts({
+        casino: this.casino,
+        pendingChange: this.configChange(),
+        authority: this.admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+  }
+
+  async executeIx() {
+    return this.program.methods
+      .executeConfigChange()
+      .accounts({
+        casino: this.casino,
+        pendingChange: this.configChange(),
+        authority: this.admin.publicKey,
+      })
+      .instruction();
+  }
+
+  // Queue a fee split change and execute it once the timelock passes
+  async setFeeSplit(feeSplit: FeeSplit) {
+    await this.send([await this.queueIx(configParams({ feeSplit }))]);
+    await this.advance(CONFIG_CHANGE_DELAY);
+    await this.send([await this.executeIx()]);
+  }
+
+  // Rounds and bets
+
+  // Start the next round, returns its id
+  async startRound(): Promise<number> {
+    const id = (await this.fetchCasino()).currentGameId.toNumber();
+    await this.send([
+      await this.program.methods
+        .requestCrashVrf()
+        .accounts({
+          casino: this.casino,
+          currentGame: id > 0 ? this.gamePda(id) : null,
+          game: this.gamePda(id + 1),
+          authority: this.admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          vrfAccount: SystemProgram.programId,
+          oracleQueue: SystemProgram.programId,
+          programState: SystemProgram.programId,
+          escrow: SystemProgram.programId,
+        })
+        .instruction(),
+    ]);
+    return id + 1;
+  }
+
+  // Resolve the current round with randomness filled with `byte`.
+  // 0x00 crashes at 1.01x, 0xff at about 99x.
+  async resolve(byte: number) {
+    const id = (await this.fetchCasino()).currentGameId.toNumber();
+    await this.send([
+      await this.program.methods
+        .resolveCrash(new Array(32).fill(byte))
+        .accounts({
+          casino: this.casino,
+          game: this.gamePda(id),
+          authority: this.admin.publicKey,
+        })
+        .instruction(),
+    ]);
+  }
+
+  async placeBetIx(player: PublicKey, tokenAccount: PublicKey, id: number, amount: number, auto: number | null = null) {
+    return this.program.methods
+      .placeBet(new BN(amount), auto === null ? null : new BN(auto))
+      .accounts({
+        casino: this.casino,
+        game: this.gamePda(id),
+        bet: this.betPda(player, id),
+        playerLimits: this.limitsPda(player),
+        playerProfile: this.profilePda(player),
+        playerAttestation: null,
+        player,
+        playerTokenAccount: tokenAccount,
+        vault: this.vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+  }
+
+  async cashoutIx(player: PublicKey, id: number, multiplier: number) {
+    return this.program.methods
+      .cashout(new BN(multiplier))
+      .accounts({
+        casino: this.casino,
+        game: this.gamePda(id),
+        bet: this.betPda(player, id),
+        player,
+      })
+      .instruction();
+  }
+
+  async settleIx(player: PublicKey, tokenAccount: PublicKey, id: number, referrer: PublicKey | null = null) {
+    return this.program.methods
+      .settleBet()
+      .accounts({
+        casino: this.casino,
+        game: this.gamePda(id),
+        bet: this.betPda(player, id),
+        playerProfile: this.profilePda(player),
+        referrerAccount: referrer === null ? null : this.referrerPda(referrer),
+        playerLimits: this.limitsPda(player),
+        playerTokenAccount: tokenAccount,
+        vault: this.vault,
+        feeVault: this.feeVault,
+        payer: this.admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+  }
+
+  async voidIx(id: number) {
+    return this.program.methods
+      .voidRound()
+      .accounts({ casino: this.casino, game: this.gamePda(id) })
+      .instruction();
+  }
+
+  async refundIx(player: PublicKey, tokenAccount: PublicKey, id: number) {
+    return this.program.methods
+      .refundBet()
+      .accounts({
+        casino: this.casino,
+        bet: this.betPda(player, id),
+        game: this.gamePda(id),
+        playerProfile: this.profilePda(player),
+        playerLimits: this.limitsPda(player),
+        playerTokenAccount: tokenAccount,
+        vault: this.vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+  }
+
+  // Player limits
+
+  async setLimitsIx(player: PublicKey, limits: LimitSettings) {
+    return this.program.methods
+      .setPlayerLimits(limits)
+      .accounts({
+        casino: this.casino,
+        playerLimits: this.limitsPda(player),
+        player,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+  }
+
+  async selfExcludeIx(player: PublicKey) {
+    return this.program.methods
+      .selfExclude()
+      .accounts({
+        casino: this.casino,
+        playerLimits: this.limitsPda(player),
+        player,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+  }
+
+  // Liquidity and staking
+
+  async initLpMint() {
+    await this.send([
+      await this.program.methods
+        .initializeLpMint()
+        .accounts({
+          casino: this.casino,
+          lpMint: this.lpMint(),
+          vault: this.vault,
+          lpEscrow: this.lpEscrow(),
+          betMint: this.mint,
+          authority: this.admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .instruction(),
+    ]);
+  }
+
+  async initStakingVault(stakingMint: PublicKey) {
+    await this.send([
+      await this.program.methods
+        .initializeStakingVault()
+        .accounts({
+          casino: this.casino,
+          stakingVault: this.stakingVault(),
+          stakingMint,
+          authority: this.admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .instruction(),
+    ]);
+  }
+
+  async depositIx(provider: PublicKey, tokenAccount: PublicKey, lpAccount: PublicKey, amount: number) {
+    return this.program.methods
+      .depositLiquidity(new BN(amount))
+      .accounts({
+        casino: this.casino,
+        provider,
+        providerTokenAccount: tokenAccount,
+        providerLpAccount: lpAccount,
+        vault: this.vault,
+        lpMint: this.lpMint(),
+        lpEscrow: this.lpEscrow(),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+  }
+
+  async withdrawIx(provider: PublicKey, tokenAccount: PublicKey, lpAccount: PublicKey, lpAmount: number) {
+    return this.program.methods
+      .withdrawLiquidity(new BN(lpAmount))
+      .accounts({
+        casino: this.casino,
+        provider,
+        providerTokenAccount: tokenAccount,
+        providerLpAccount: lpAccount,
+        vault: this.vault,
+        lpMint: this.lpMint(),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+  }
+
+  async stakeIx(staker: PublicKey, lpAccount: PublicKey, amount: number) {
+    return this.program.methods
+      .stakeLp(new BN(amount))
+      .accounts({
+        casino: this.casino,
+        stake: this.stakePda(staker),
+        staker,
+        stakerLpAccount: lpAccount,
+        stakingVault: this.stakingVault(),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+  }
+
+  // Accounts shared by unstake, complete and emergency unstake
+  unstakeAccounts(staker: PublicKey, lpAccount: PublicKey, lpMint: PublicKey | null = null) {
+    return {
+      casino: this.casino,
+      stake: this.stakePda(staker),
+      staker,
+      positionTokenAccount: null,
+      stakerLpAccount: lpAccount,
+      stakingVault: this.stakingVault(),
+      vault: this.vault,
+      lpMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+  }
+
+  async requestUnstakeIx(staker: PublicKey, amount: number) {
+    return this.program.methods
+      .requestUnstake(new BN(amount))
+      .accounts({
+        casino: this.casino,
+        stake: this.stakePda(staker),
+        staker,
+        positionTokenAccount: null,
+      })
+      .instruction();
+  }
+
+  // Accounts for lock and extend
+  lockAccounts(staker: PublicKey) {
+    return {
+      casino: this.casino,
+      stake: this.stakePda(staker),
+      staker,
+      positionTokenAccount: null,
+    };
+  }
+
+  // A staker holding `amount` of a fresh external LP mint used as the staking mint
+  async externalStaker(amount: number): Promise<[Keypair, PublicKey]> {
+    const lpMint = await this.createMint();
+    await this.initStakingVault(lpMint);
+    const [staker] = await this.player();
+    const lpAccount = await this.tokenAccount(lpMint, staker.publicKey);
+    await this.mintTo(lpMint, lpAccount, amount);
+    return [staker, lpAccount];
+  }
+}
+
+export function le(id: number): Buffer {
+  return new BN(id).toArrayLike(Buffer, "le", 8);
+}
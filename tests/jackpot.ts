@@ -0,0 +1,64 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+import { Env, TOKEN } from "./helpers";
+
+async function settleJackpotIx(env: Env, player: PublicKey, tokenAccount: PublicKey, id: number) {
+  return env.program.methods
+    .settleJackpot()
+    .accounts({
+      casino: env.casino,
+      game: env.gamePda(id),
+      bet: env.betPda(player, id),
+      playerTokenAccount: tokenAccount,
+      feeVault: env.feeVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .instruction();
+}
+
+describe("jackpot", () => {
+  it("Splits a jackpot by stake and returns refunded shares to the pool", async () => {
+    const env = await Env.create();
+    await env.setFeeSplit({ stakerBps: 5000, treasuryBps: 0, referralBps: 0, jackpotBps: 5000 });
+    await env.send([
+      await env.program.methods.setJackpotTrigger(new BN(500000)).accounts(env.configAccounts()).instruction(),
+    ]);
+    const [a, aTokenAccount] = await env.player(200 * TOKEN);
+    const [b, bTokenAccount] = await env.player(200 * TOKEN);
+
+    // Half the fee of a lost bet feeds the pool
+    let id = await env.startRound();
+    await env.send([await env.placeBetIx(a.publicKey, aTokenAccount, id, 100 * TOKEN)], [a]);
+    await env.resolve(0x00);
+    await env.send([await env.settleIx(a.publicKey, aTokenAccount, id)]);
+    expect((await env.fetchCasino()).jackpotPool.toNumber()).to.equal(TOKEN);
+    expect(await env.balance(env.feeVault)).to.equal(TOKEN);
+    await env.fails([await settleJackpotIx(env, a.publicKey, aTokenAccount, id)], [], "NoJackpot");
+
+    // A crash past the trigger awards the pool to the round's stakes
+    id = await env.startRound();
+    await env.send([await env.placeBetIx(a.publicKey, aTokenAccount, id, 10 * TOKEN)], [a]);
+    await env.send([await env.placeBetIx(b.publicKey, bTokenAccount, id, 30 * TOKEN)], [b]);
+    await env.resolve(0xff);
+    let game = await env.program.account.game.fetch(env.gamePda(id));
+    expect(game.jackpotAmount.toNumber()).to.equal(TOKEN);
+    expect((await env.fetchCasino()).jackpotPool.toNumber()).to.equal(0);
+
+    const before = await env.balance(aTokenAccount);
+    await env.send([await settleJackpotIx(env, a.publicKey, aTokenAccount, id)]);
+    expect(await env.balance(aTokenAccount)).to.equal(before + 250_000);
+    await env.fails([await settleJackpotIx(env, a.publicKey, aTokenAccount, id)], [], "JackpotAlreadySettled");
+
+    // A refunded stake gives up its share
+    await env.send([await env.program.methods.shutdown().accounts(env.configAccounts()).instruction()]);
+    await env.send([await env.refundIx(b.publicKey, bTokenAccount, id)]);
+    expect(await env.balance(bTokenAccount)).to.equal(200 * TOKEN);
+    expect((await env.fetchCasino()).jackpotPool.toNumber()).to.equal(750_000);
+    game = await env.program.account.game.fetch(env.gamePda(id));
+    expect(game.jackpotAmount.toNumber()).to.equal(250_000);
+    expect(game.totalWagered.toNumber()).to.equal(10 * TOKEN);
+    await env.fails([await settleJackpotIx(env, b.publicKey, bTokenAccount, id)], [], "InvalidBetStatus");
+  });
+});
@@ -0,0 +1,62 @@
+import { BN } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import {
+  DEFAULT_VRF_TIMEOUT_SLOTS,
+  Env,
+  LIMIT_LOOSEN_DELAY,
+  SELF_EXCLUSION_PERIOD,
+  TOKEN,
+  limitSettings,
+} from "./helpers";
+
+describe("limits", () => {
+  it("Takes refunded stakes back out of the daily totals", async () => {
+    const env = await Env.create();
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    await env.send(
+      [await env.setLimitsIx(player.publicKey, limitSettings({ dailyLossLimit: new BN(50 * TOKEN) }))],
+      [player]
+    );
+
+    let id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 40 * TOKEN)], [player]);
+    await env.advanceSlots(DEFAULT_VRF_TIMEOUT_SLOTS);
+    await env.send([await env.voidIx(id)]);
+    await env.send([await env.refundIx(player.publicKey, tokenAccount, id)]);
+    const limits = await env.program.account.playerLimits.fetch(env.limitsPda(player.publicKey));
+    expect(limits.lostToday.toNumber()).to.equal(0);
+    expect(limits.wageredToday.toNumber()).to.equal(0);
+
+    // A real loss still counts against the limit
+    id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 40 * TOKEN)], [player]);
+    await env.resolve(0x00);
+    await env.send([await env.settleIx(player.publicKey, tokenAccount, id)]);
+    id = await env.startRound();
+    await env.fails(
+      [await env.placeBetIx(player.publicKey, tokenAccount, id, 20 * TOKEN)],
+      [player],
+      "DailyLossLimitExceeded"
+    );
+  });
+
+  it("Keeps a self-exclusion for its full period", async () => {
+    const env = await Env.create();
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    await env.send([await env.selfExcludeIx(player.publicKey)], [player]);
+    let limits = await env.program.account.playerLimits.fetch(env.limitsPda(player.publicKey));
+    const until = env.now + SELF_EXCLUSION_PERIOD;
+    expect(limits.limits.selfExcludedUntil.toNumber()).to.equal(until);
+
+    // Loosening the limits does not lift the exclusion
+    await env.send([await env.setLimitsIx(player.publicKey, limitSettings())], [player]);
+    await env.advance(LIMIT_LOOSEN_DELAY);
+    const id = await env.startRound();
+    await env.fails([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player], "SelfExcluded");
+    limits = await env.program.account.playerLimits.fetch(env.limitsPda(player.publicKey));
+    expect(limits.limits.selfExcludedUntil.toNumber()).to.equal(until);
+
+    await env.advance(SELF_EXCLUSION_PERIOD - LIMIT_LOOSEN_DELAY);
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player]);
+  });
+});
@@ -0,0 +1,31 @@
+import { expect } from "chai";
+import { Env, TOKEN } from "./helpers";
+
+describe("liquidity", () => {
+  it("Locks the minimum liquidity on the first deposit", async () => {
+    const env = await Env.create();
+    await env.initLpMint();
+    const [provider, tokenAccount] = await env.player(1000 * TOKEN);
+    const lpAccount = await env.tokenAccount(env.lpMint(), provider.publicKey);
+
+    // The first deposit must cover the locked shares
+    await env.fails(
+      [await env.depositIx(provider.publicKey, tokenAccount, lpAccount, 1000)],
+      [provider],
+      "InvalidLiquidityAmount"
+    );
+
+    await env.send([await env.depositIx(provider.publicKey, tokenAccount, lpAccount, 500 * TOKEN)], [provider]);
+    expect(await env.balance(lpAccount)).to.equal(500 * TOKEN - 1000);
+    expect(await env.balance(env.lpEscrow())).to.equal(1000);
+    expect(await env.balance(env.vault)).to.equal(500 * TOKEN);
+
+    // Withdrawing every provider share leaves the locked liquidity behind
+    await env.send(
+      [await env.withdrawIx(provider.publicKey, tokenAccount, lpAccount, 500 * TOKEN - 1000)],
+      [provider]
+    );
+    expect(await env.balance(tokenAccount)).to.equal(1000 * TOKEN - 1000);
+    expect(await env.balance(env.vault)).to.equal(1000);
+  });
+});
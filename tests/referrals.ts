@@ -0,0 +1,61 @@
+import { expect } from "chai";
+import { SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Env, TOKEN } from "./helpers";
+
+describe("referrals", () => {
+  it("Pays a referrer's earned credit once", async () => {
+    const env = await Env.create();
+    await env.setFeeSplit({ stakerBps: 8000, treasuryBps: 0, referralBps: 2000, jackpotBps: 0 });
+    await env.send([
+      await env.program.methods.setReferralRates([1000, 0, 0, 0]).accounts(env.configAccounts()).instruction(),
+    ]);
+    const [referrer, referrerTokenAccount] = await env.player();
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    await env.send(
+      [
+        await env.program.methods
+          .registerReferrer(referrer.publicKey)
+          .accounts({
+            casino: env.casino,
+            playerProfile: env.profilePda(player.publicKey),
+            referrerAccount: env.referrerPda(referrer.publicKey),
+            player: player.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .instruction(),
+      ],
+      [player]
+    );
+
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player]);
+    await env.resolve(0x00);
+    await env.fails([await env.settleIx(player.publicKey, tokenAccount, id)], [], "ReferrerAccountRequired");
+    await env.send([await env.settleIx(player.publicKey, tokenAccount, id, referrer.publicKey)]);
+
+    // The referrer earns 10% of the 0.2 fee, the unearned referral share goes to the treasury with no stakers
+    let casino = await env.fetchCasino();
+    expect(casino.referralPool.toNumber()).to.equal(20_000);
+    expect(casino.treasuryPool.toNumber()).to.equal(20_000);
+    expect(await env.balance(env.feeVault)).to.equal(40_000);
+
+    const claim = async () =>
+      env.program.methods
+        .claimReferralRewards()
+        .accounts({
+          casino: env.casino,
+          referrerAccount: env.referrerPda(referrer.publicKey),
+          referrer: referrer.publicKey,
+          referrerTokenAccount,
+          feeVault: env.feeVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+    await env.send([await claim()], [referrer]);
+    expect(await env.balance(referrerTokenAccount)).to.equal(20_000);
+    casino = await env.fetchCasino();
+    expect(casino.referralPool.toNumber()).to.equal(0);
+    await env.fails([await claim()], [referrer], "NoRewards");
+  });
+});
@@ -0,0 +1,101 @@
+import { expect } from "chai";
+import { DEFAULT_VRF_TIMEOUT_SLOTS, Env, PAUSE_BETS, TOKEN } from "./helpers";
+
+describe("rounds", () => {
+  it("Rejects a cashout above the auto-cashout", async () => {
+    const env = await Env.create();
+    await env.mintTo(env.mint, env.vault, 1000 * TOKEN);
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN, 20000)], [player]);
+
+    // The live multiplier is past 3x, but the bet would have left at 2x
+    await env.advance(30);
+    await env.fails([await env.cashoutIx(player.publicKey, id, 30000)], [player], "AboveAutoCashout");
+    await env.send([await env.cashoutIx(player.publicKey, id, 15000)], [player]);
+
+    const bet = await env.program.account.bet.fetch(env.betPda(player.publicKey, id));
+    expect(bet.cashoutMultiplier?.toNumber()).to.equal(15000);
+  });
+
+  it("Locks a live cashout and pays it on settlement", async () => {
+    const env = await Env.create();
+    await env.mintTo(env.mint, env.vault, 1000 * TOKEN);
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player]);
+
+    // Ten seconds in the live multiplier is below 2x
+    await env.advance(10);
+    await env.fails([await env.cashoutIx(player.publicKey, id, 20000)], [player], "MultiplierNotReached");
+    await env.send([await env.cashoutIx(player.publicKey, id, 15000)], [player]);
+    await env.fails([await env.cashoutIx(player.publicKey, id, 15000)], [player], "CashoutAlreadyLocked");
+
+    await env.resolve(0xff);
+    await env.send([await env.settleIx(player.publicKey, tokenAccount, id)]);
+    // 15 gross less the 2% fee
+    expect(await env.balance(tokenAccount)).to.equal(90 * TOKEN + 14_700_000);
+  });
+
+  it("Loses a locked cashout when the round crashes below it", async () => {
+    const env = await Env.create();
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player]);
+    await env.advance(10);
+    await env.send([await env.cashoutIx(player.publicKey, id, 15000)], [player]);
+
+    await env.resolve(0x00);
+    await env.send([await env.settleIx(player.publicKey, tokenAccount, id)]);
+    expect(await env.balance(tokenAccount)).to.equal(90 * TOKEN);
+    const bet = await env.program.account.bet.fetch(env.betPda(player.publicKey, id));
+    expect(bet.status).to.deep.equal({ lost: {} });
+  });
+
+  it("Refunds the bets of a round voided after the randomness timeout", async () => {
+    const env = await Env.create();
+    const [player, tokenAccount] = await env.player(100 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 10 * TOKEN)], [player]);
+
+    await env.fails([await env.refundIx(player.publicKey, tokenAccount, id)], [], "BetNotRefundable");
+    await env.fails([await env.voidIx(id)], [], "RandomnessNotExpired");
+
+    await env.advanceSlots(DEFAULT_VRF_TIMEOUT_SLOTS);
+    await env.send([await env.voidIx(id)]);
+    await env.send([await env.refundIx(player.publicKey, tokenAccount, id)]);
+    expect(await env.balance(tokenAccount)).to.equal(100 * TOKEN);
+
+    const casino = await env.fetchCasino();
+    expect(casino.refundLiability.toNumber()).to.equal(0);
+    expect(casino.roundExposure.toNumber()).to.equal(0);
+
+    // The voided round no longer blocks the next one
+    expect(await env.startRound()).to.equal(id + 1);
+  });
+
+  it("Pauses bets when the window's losses pass the breaker threshold", async () => {
+    const env = await Env.create();
+    await env.mintTo(env.mint, env.vault, 1000 * TOKEN);
+    const [player, tokenAccount] = await env.player(200 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, id, 100 * TOKEN, 50000)], [player]);
+
+    // A 490 payout against a 100 stake loses 39% of the 1000 bankroll
+    await env.resolve(0xff);
+    await env.send([await env.settleIx(player.publicKey, tokenAccount, id)]);
+    let casino = await env.fetchCasino();
+    expect(casino.pauseFlags & PAUSE_BETS).to.equal(PAUSE_BETS);
+    expect(casino.breakerWindowBankroll.toNumber()).to.equal(1000 * TOKEN);
+    expect(casino.breakerNetFlow.toNumber()).to.equal(-390 * TOKEN);
+
+    const next = await env.startRound();
+    await env.fails([await env.placeBetIx(player.publicKey, tokenAccount, next, 10 * TOKEN)], [player], "GamePaused");
+
+    // Unpausing starts a fresh window
+    await env.send([await env.program.methods.unpause(PAUSE_BETS).accounts(env.configAccounts()).instruction()]);
+    await env.send([await env.placeBetIx(player.publicKey, tokenAccount, next, 10 * TOKEN)], [player]);
+    casino = await env.fetchCasino();
+    expect(casino.breakerNetFlow.toNumber()).to.equal(10 * TOKEN);
+  });
+});
@@ -1,7 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { SolanaCrashGame } from "../target/types/solana_crash_game";
-import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from "@solana/spl-token";
 import { expect } from "chai";
 
@@ -18,6 +18,7 @@ describe("solana-crash-game", () => {
   let casinoBump: number;
   let mint: PublicKey;
   let vault: PublicKey;
+  let feeVault: PublicKey;
   let playerTokenAccount: PublicKey;
 
   before(async () => {
@@ -66,6 +67,12 @@ describe("solana-crash-game", () => {
       [Buffer.from("vault"), casino.toBuffer()],
       program.programId
     );
+
+    // Derive fee vault PDA
+    [feeVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault"), casino.toBuffer()],
+      program.programId
+    );
   });
 
   it("Initializes casino", async () => {
@@ -83,30 +90,100 @@ describe("solana-crash-game", () => {
       .rpc();
 
     const casinoAccount = await program.account.casino.fetch(casino);
-    expect(casinoAccount.houseEdgeBps.toNumber()).to.equal(houseEdgeBps);
+    expect(casinoAccount.houseEdgeBps).to.equal(houseEdgeBps);
     expect(casinoAccount.minBet.toNumber()).to.equal(minBet.toNumber());
     expect(casinoAccount.maxBet.toNumber()).to.equal(maxBet.toNumber());
-    expect(casinoAccount.isPaused).to.be.false;
+    expect(casinoAccount.pauseFlags).to.equal(0);
+  });
+
+  it("Initializes the vaults", async () => {
+    await program.methods
+      .initializeVault()
+      .accounts({
+        casino,
+        vault,
+        betMint: mint,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    await program.methods
+      .initializeFeeVault()
+      .accounts({
+        casino,
+        feeVault,
+        vault,
+        betMint: mint,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const vaultAccount = await provider.connection.getAccountInfo(vault);
+    expect(vaultAccount.owner.toString()).to.equal(TOKEN_PROGRAM_ID.toString());
+    const feeVaultAccount = await provider.connection.getAccountInfo(feeVault);
+    expect(feeVaultAccount.owner.toString()).to.equal(TOKEN_PROGRAM_ID.toString());
   });
 
   it("Places a bet", async () => {
     const betAmount = new anchor.BN(10000000); // 0.01 tokens
+    const gameId = Buffer.from(new anchor.BN(1).toArray("le", 8));
+
+    // Bets join the round started by the operator's randomness request
+    const [game] = PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), casino.toBuffer(), gameId],
+      program.programId
+    );
+
+    await program.methods
+      .requestCrashVrf()
+      .accounts({
+        casino,
+        currentGame: null,
+        game,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        vrfAccount: SystemProgram.programId,
+        oracleQueue: SystemProgram.programId,
+        programState: SystemProgram.programId,
+        escrow: SystemProgram.programId,
+      })
+      .rpc();
 
     const [bet] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("bet"),
         casino.toBuffer(),
         player.publicKey.toBuffer(),
-        Buffer.from(new anchor.BN(0).toArray("le", 8)),
+        gameId,
       ],
       program.programId
     );
 
+    const [playerLimits] = PublicKey.findProgramAddressSync(
+      [Buffer.from("player_limits"), casino.toBuffer(), player.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [playerProfile] = PublicKey.findProgramAddressSync(
+      [Buffer.from("player_profile"), casino.toBuffer(), player.publicKey.toBuffer()],
+      program.programId
+    );
+
     const tx = await program.methods
       .placeBet(betAmount, null)
       .accounts({
         casino,
+        game,
         bet,
+        playerLimits,
+        playerProfile,
+        playerAttestation: null,
         player: player.publicKey,
         playerTokenAccount,
         vault,
@@ -119,26 +196,56 @@ describe("solana-crash-game", () => {
     const betAccount = await program.account.bet.fetch(bet);
     expect(betAccount.amount.toNumber()).to.equal(betAmount.toNumber());
     expect(betAccount.player.toString()).to.equal(player.publicKey.toString());
+
+    const profileAccount = await program.account.playerProfile.fetch(playerProfile);
+    expect(profileAccount.betsPlaced.toNumber()).to.equal(1);
+    expect(profileAccount.totalWagered.toNumber()).to.equal(betAmount.toNumber());
+
+    const gameAccount = await program.account.game.fetch(game);
+    expect(gameAccount.totalWagered.toNumber()).to.equal(betAmount.toNumber());
   });
 
-  it("Updates house edge (admin only)", async () => {
+  it("Queues a house edge change behind the timelock (admin only)", async () => {
     const newHouseEdge = 300; // 3%
 
+    const [pendingChange] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config_change"), casino.toBuffer()],
+      program.programId
+    );
+
     await program.methods
-      .updateHouseEdge(newHouseEdge)
+      .queueConfigChange({
+        houseEdgeBps: newHouseEdge,
+        minBet: null,
+        maxBet: null,
+        feeSplit: null,
+        curveScale: null,
+        maxPayout: null,
+        maxExposureBps: null,
+        bettingWindow: null,
+        keeperRewardBps: null,
+      })
       .accounts({
         casino,
+        pendingChange,
         authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
 
+    const pendingAccount = await program.account.pendingConfigChange.fetch(pendingChange);
+    expect(pendingAccount.params.houseEdgeBps).to.equal(newHouseEdge);
+
+    // Edge is unchanged until the delay passes and the change is executed
     const casinoAccount = await program.account.casino.fetch(casino);
-    expect(casinoAccount.houseEdgeBps.toNumber()).to.equal(newHouseEdge);
+    expect(casinoAccount.houseEdgeBps).to.not.equal(newHouseEdge);
   });
 
-  it("Pauses game (admin only)", async () => {
+  it("Pauses new bets only", async () => {
+    const PAUSE_BETS = 1;
+
     await program.methods
-      .setPause(true)
+      .pause(PAUSE_BETS)
       .accounts({
         casino,
         authority: authority.publicKey,
@@ -146,6 +253,6 @@ describe("solana-crash-game", () => {
       .rpc();
 
     const casinoAccount = await program.account.casino.fetch(casino);
-    expect(casinoAccount.isPaused).to.be.true;
+    expect(casinoAccount.pauseFlags).to.equal(PAUSE_BETS);
   });
 });
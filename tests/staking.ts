@@ -0,0 +1,223 @@
+import { BN } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Env, MIN_LOCK_DURATION, PAUSE_WITHDRAWALS, TOKEN } from "./helpers";
+
+describe("staking", () => {
+  it("Unstakes an external LP token without the bankroll LP mint", async () => {
+    const env = await Env.create();
+    const [staker, lpAccount] = await env.externalStaker(100 * TOKEN);
+    await env.send([await env.stakeIx(staker.publicKey, lpAccount, 100 * TOKEN)], [staker]);
+
+    await env.send(
+      [
+        await env.program.methods
+          .unstakeLp(new BN(100 * TOKEN))
+          .accounts(env.unstakeAccounts(staker.publicKey, lpAccount))
+          .instruction(),
+      ],
+      [staker]
+    );
+    expect(await env.balance(lpAccount)).to.equal(100 * TOKEN);
+  });
+
+  it("Holds unstakes behind the cooldown", async () => {
+    const env = await Env.create();
+    const [staker, lpAccount] = await env.externalStaker(100 * TOKEN);
+    await env.send([
+      await env.program.methods.setUnstakeCooldown(new BN(3600)).accounts(env.configAccounts()).instruction(),
+    ]);
+    await env.send([await env.stakeIx(staker.publicKey, lpAccount, 100 * TOKEN)], [staker]);
+    const accounts = env.unstakeAccounts(staker.publicKey, lpAccount);
+
+    await env.fails(
+      [await env.program.methods.unstakeLp(new BN(10 * TOKEN)).accounts(accounts).instruction()],
+      [staker],
+      "UnstakeCooldownRequired"
+    );
+    await env.fails(
+      [await env.program.methods.completeUnstake().accounts(accounts).instruction()],
+      [staker],
+      "NoUnstakeRequest"
+    );
+
+    await env.send([await env.requestUnstakeIx(staker.publicKey, 40 * TOKEN)], [staker]);
+    await env.advance(3599);
+    await env.fails(
+      [await env.program.methods.completeUnstake().accounts(accounts).instruction()],
+      [staker],
+      "UnstakeCooldownActive"
+    );
+
+    await env.advance(1);
+    await env.send([await env.program.methods.completeUnstake().accounts(accounts).instruction()], [staker]);
+    expect(await env.balance(lpAccount)).to.equal(40 * TOKEN);
+    const stake = await env.program.account.stake.fetch(env.stakePda(staker.publicKey));
+    expect(stake.amount.toNumber()).to.equal(60 * TOKEN);
+    expect(stake.unstakeAmount.toNumber()).to.equal(0);
+  });
+
+  it("Locks a stake until its lock ends", async () => {
+    const env = await Env.create();
+    const [staker, lpAccount] = await env.externalStaker(100 * TOKEN);
+    await env.send([await env.stakeIx(staker.publicKey, lpAccount, 100 * TOKEN)], [staker]);
+    const lock = env.lockAccounts(staker.publicKey);
+
+    await env.fails(
+      [await env.program.methods.lockStake(new BN(MIN_LOCK_DURATION - 1)).accounts(lock).instruction()],
+      [staker],
+      "InvalidLockDuration"
+    );
+    await env.send([await env.program.methods.lockStake(new BN(MIN_LOCK_DURATION)).accounts(lock).instruction()], [staker]);
+
+    // The lock boosts the stake's reward weight
+    const stake = await env.program.account.stake.fetch(env.stakePda(staker.publicKey));
+    expect(stake.lockEnd.toNumber()).to.equal(env.now + MIN_LOCK_DURATION);
+    expect(stake.weight.gt(stake.amount)).to.be.true;
+    expect((await env.fetchCasino()).totalWeight.eq(stake.weight)).to.be.true;
+
+    await env.fails(
+      [await env.program.methods.lockStake(new BN(MIN_LOCK_DURATION)).accounts(lock).instruction()],
+      [staker],
+      "LockAlreadyActive"
+    );
+    const accounts = env.unstakeAccounts(staker.publicKey, lpAccount);
+    await env.fails(
+      [await env.program.methods.unstakeLp(new BN(10 * TOKEN)).accounts(accounts).instruction()],
+      [staker],
+      "StakeLocked"
+    );
+    await env.fails([await env.requestUnstakeIx(staker.publicKey, 10 * TOKEN)], [staker], "StakeLocked");
+
+    await env.advance(MIN_LOCK_DURATION);
+    await env.send([await env.program.methods.unstakeLp(new BN(100 * TOKEN)).accounts(accounts).instruction()], [staker]);
+    expect(await env.balance(lpAccount)).to.equal(100 * TOKEN);
+    await env.fails(
+      [await env.program.methods.extendLock(new BN(MIN_LOCK_DURATION)).accounts(lock).instruction()],
+      [staker],
+      "NoActiveLock"
+    );
+  });
+
+  it("Compounds staker rewards into bankroll shares priced on the bankroll", async () => {
+    const env = await Env.create();
+    await env.initLpMint();
+    const lpMint = env.lpMint();
+    await env.initStakingVault(lpMint);
+    const [staker, tokenAccount] = await env.player(1000 * TOKEN);
+    const lpAccount = await env.tokenAccount(lpMint, staker.publicKey);
+    await env.send([await env.depositIx(staker.publicKey, tokenAccount, lpAccount, 500 * TOKEN)], [staker]);
+    await env.send([await env.stakeIx(staker.publicKey, lpAccount, 100 * TOKEN)], [staker]);
+
+    // A lost bet pays its fee to the stakers
+    const [player, playerTokenAccount] = await env.player(100 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, playerTokenAccount, id, 10 * TOKEN)], [player]);
+    await env.resolve(0x00);
+    await env.send([await env.settleIx(player.publicKey, playerTokenAccount, id)]);
+    expect(await env.balance(env.feeVault)).to.equal(200_000);
+    expect((await env.fetchCasino()).stakerPool.toNumber()).to.equal(200_000);
+
+    const vaultBefore = await env.balance(env.vault);
+    const supplyBefore = await env.supply(lpMint);
+    await env.send(
+      [
+        await env.program.methods
+          .compoundRewards()
+          .accounts({
+            casino: env.casino,
+            stake: env.stakePda(staker.publicKey),
+            caller: staker.publicKey,
+            positionTokenAccount: null,
+            keeperTokenAccount: null,
+            vault: env.vault,
+            feeVault: env.feeVault,
+            stakingVault: env.stakingVault(),
+            lpMint,
+            lpEscrow: env.lpEscrow(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .instruction(),
+      ],
+      [staker]
+    );
+
+    const stake = await env.program.account.stake.fetch(env.stakePda(staker.publicKey));
+    const rewards = stake.claimedRewards.toNumber();
+    expect(rewards).to.be.greaterThan(0).and.at.most(200_000);
+    expect(await env.balance(env.vault)).to.equal(vaultBefore + rewards);
+
+    // Shares are minted at the bankroll's price before the rewards land
+    const minted = (await env.supply(lpMint)) - supplyBefore;
+    expect(minted).to.equal(Math.floor((rewards * supplyBefore) / vaultBefore));
+    expect(stake.amount.toNumber()).to.equal(100 * TOKEN + minted);
+    expect(await env.balance(env.stakingVault())).to.equal(100 * TOKEN + minted);
+  });
+
+  it("Allows emergency unstakes of a locked stake only while withdrawals are paused", async () => {
+    const env = await Env.create();
+    const [staker, lpAccount] = await env.externalStaker(100 * TOKEN);
+    await env.send([await env.stakeIx(staker.publicKey, lpAccount, 100 * TOKEN)], [staker]);
+    await env.send(
+      [
+        await env.program.methods
+          .lockStake(new BN(MIN_LOCK_DURATION))
+          .accounts(env.lockAccounts(staker.publicKey))
+          .instruction(),
+      ],
+      [staker]
+    );
+
+    // A lost bet accrues fees to the locked stake
+    const [player, playerTokenAccount] = await env.player(100 * TOKEN);
+    const id = await env.startRound();
+    await env.send([await env.placeBetIx(player.publicKey, playerTokenAccount, id, 10 * TOKEN)], [player]);
+    await env.resolve(0x00);
+    await env.send([await env.settleIx(player.publicKey, playerTokenAccount, id)]);
+
+    const accounts = env.unstakeAccounts(staker.publicKey, lpAccount);
+    await env.fails(
+      [await env.program.methods.emergencyUnstake().accounts(accounts).instruction()],
+      [staker],
+      "StakeLocked"
+    );
+
+    await env.send([await env.program.methods.pause(PAUSE_WITHDRAWALS).accounts(env.configAccounts()).instruction()]);
+    await env.send([await env.program.methods.emergencyUnstake().accounts(accounts).instruction()], [staker]);
+    expect(await env.balance(lpAccount)).to.equal(100 * TOKEN);
+
+    const stake = await env.program.account.stake.fetch(env.stakePda(staker.publicKey));
+    expect(stake.amount.toNumber()).to.equal(0);
+    expect(stake.pendingRewards.toNumber()).to.equal(0);
+    expect(stake.lockedRewards.toNumber()).to.equal(0);
+    expect(stake.weight.toNumber()).to.equal(0);
+
+    // Forfeited rewards go to the treasury, only rounding dust stays with the stakers
+    const casino = await env.fetchCasino();
+    expect(casino.totalStaked.toNumber()).to.equal(0);
+    expect(casino.totalWeight.toNumber()).to.equal(0);
+    expect(casino.treasuryPool.toNumber()).to.be.greaterThan(0);
+    expect(casino.stakerPool.add(casino.treasuryPool).toNumber()).to.equal(200_000);
+  });
+
+  it("Holds emergency unstakes behind the cooldown outside an emergency", async () => {
+    const env = await Env.create();
+    const [staker, lpAccount] = await env.externalStaker(100 * TOKEN);
+    await env.send([
+      await env.program.methods.setUnstakeCooldown(new BN(3600)).accounts(env.configAccounts()).instruction(),
+    ]);
+    await env.send([await env.stakeIx(staker.publicKey, lpAccount, 100 * TOKEN)], [staker]);
+    const accounts = env.unstakeAccounts(staker.publicKey, lpAccount);
+
+    await env.fails(
+      [await env.program.methods.emergencyUnstake().accounts(accounts).instruction()],
+      [staker],
+      "UnstakeCooldownActive"
+    );
+
+    await env.send([await env.requestUnstakeIx(staker.publicKey, 100 * TOKEN)], [staker]);
+    await env.advance(3600);
+    await env.send([await env.program.methods.emergencyUnstake().accounts(accounts).instruction()], [staker]);
+    expect(await env.balance(lpAccount)).to.equal(100 * TOKEN);
+  });
+});